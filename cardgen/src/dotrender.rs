@@ -0,0 +1,55 @@
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::cardrender::{HEIGHT, WIDTH};
+use crate::SetColor;
+
+/// Largest number of dots a card can have, one for each color we can render.
+pub const MAX_DOTS: usize = 6;
+
+/// Renders a Projective Set card to a bitmap. The dots are laid out in a grid of three
+/// columns and two rows, and `dots[i]` says whether the `i`th dot, drawn in the `i`th
+/// `SetColor`, is on the card. Slots of absent dots are outlined faintly.
+pub fn render_dot_card(dots: &[bool]) -> Pixmap {
+    assert!(
+        dots.len() <= MAX_DOTS,
+        "cards have at most {} dots",
+        MAX_DOTS
+    );
+
+    let mut pixmap = Pixmap::new(WIDTH, HEIGHT).unwrap();
+    let mut bg_color = tiny_skia::Color::WHITE;
+    bg_color.apply_opacity(0.5);
+    pixmap.fill(bg_color);
+
+    let radius = (HEIGHT as f32) * 0.18;
+    for (i, &present) in dots.iter().enumerate() {
+        let cx = (WIDTH as f32) * (0.2 + 0.3 * (i % 3) as f32);
+        let cy = (HEIGHT as f32) * (0.28 + 0.44 * (i / 3) as f32);
+        let circle = PathBuilder::from_circle(cx, cy, radius).unwrap();
+
+        let mut paint = Paint {
+            anti_alias: true,
+            ..Paint::default()
+        };
+        if present {
+            let color = usvg::Color::from(SetColor::index(i).unwrap());
+            paint.set_color_rgba8(color.red, color.green, color.blue, 255);
+            pixmap.fill_path(
+                &circle,
+                &paint,
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        } else {
+            paint.set_color_rgba8(0, 0, 0, 40);
+            let stroke = Stroke {
+                width: 2.0,
+                ..Stroke::default()
+            };
+            pixmap.stroke_path(&circle, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+
+    pixmap
+}
@@ -1,4 +1,12 @@
-//! The module renders Set cards with various attributes to bitmaps
+//! The module renders Set and Projective Set cards with various attributes to bitmaps
+//!
+//! The attributes implement `Display` and `FromStr`, writing cards as words such as
+//! "2 red striped hearts".
+//!
+//! With the `serde` feature enabled, the attribute types implement `Serialize` and
+//! `Deserialize`. The enums are serialized as their variant names, and the structs as
+//! objects with the same field names, e.g.
+//! `{"num": "One", "color": "Red", "shape": "Squiggle", "filling": "Solid"}`.
 
 #![forbid(unsafe_code)]
 #![cfg_attr(not(debug_assertions), deny(warnings))] // Forbid warnings in release builds
@@ -7,17 +15,27 @@
 
 mod cardrender;
 mod colorandfill;
+mod dotrender;
 mod filling_nodes;
+mod notation;
 mod randomize_attribute;
 
 pub use cardrender::render_card;
 pub use cardrender::HEIGHT as CARDHEIGHT;
 pub use cardrender::WIDTH as CARDWIDTH;
+pub use dotrender::{render_dot_card, MAX_DOTS};
 pub use filling_nodes::{generate_filling_nodes, FillingNodes};
-pub use randomize_attribute::{generate_random_attributes, generate_standard_attributes};
+pub use notation::ParseAttributeError;
+pub use randomize_attribute::{
+    generate_partially_random_attributes, generate_partially_random_attributes_with_rng,
+    generate_random_attributes, generate_random_attributes_with_rng,
+    generate_random_quad_attributes, generate_random_quad_attributes_with_rng,
+    generate_standard_attributes, generate_standard_quad_attributes, AttributeRandomization,
+};
 
 /// The four visual attributes a card can have
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CardVisualAttr {
     /// Number of elements on card
     pub num: SetNum,
@@ -31,6 +49,7 @@ pub struct CardVisualAttr {
 
 /// The six filling patterns we can render. The first three are standard.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Filling {
     Hollow,
     Solid,
@@ -42,6 +61,7 @@ pub enum Filling {
 
 /// The six colors we can render shapes in. The first three are standard.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SetColor {
     Purple,
     Red,
@@ -53,6 +73,7 @@ pub enum SetColor {
 
 /// The number of elements we can render on a card.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SetNum {
     One,
     Two,
@@ -64,6 +85,7 @@ pub enum SetNum {
 
 /// The six different shapes we can render. The first three are standard.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Shape {
     Diamond,
     Pill,
@@ -75,6 +97,7 @@ pub enum Shape {
 
 /// The attributes we can vary in any given deck
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attributes {
     pub numbers: [SetNum; 3],
     pub colors: [SetColor; 3],
@@ -82,6 +105,16 @@ pub struct Attributes {
     pub fillings: [Filling; 3],
 }
 
+/// The attributes we can vary in a deck of Quads, where each attribute takes four values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadAttributes {
+    pub numbers: [SetNum; 4],
+    pub colors: [SetColor; 4],
+    pub shapes: [Shape; 4],
+    pub fillings: [Filling; 4],
+}
+
 impl Filling {
     fn index(index: usize) -> Option<Self> {
         match index {
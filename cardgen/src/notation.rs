@@ -0,0 +1,150 @@
+//! Writing attributes as words, e.g. "2 red striped hearts"
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{CardVisualAttr, Filling, SetColor, SetNum, Shape};
+
+/// The error returned when an attribute or a card cannot be read from a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAttributeError {
+    /// What was being read, e.g. "color"
+    pub expected: &'static str,
+    /// The text that could not be read
+    pub found: String,
+}
+
+impl ParseAttributeError {
+    fn new(expected: &'static str, found: &str) -> Self {
+        Self {
+            expected,
+            found: found.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseAttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" is not a {}", self.found, self.expected)
+    }
+}
+
+impl std::error::Error for ParseAttributeError {}
+
+impl SetNum {
+    fn name(&self) -> &'static str {
+        match self {
+            SetNum::One => "1",
+            SetNum::Two => "2",
+            SetNum::Three => "3",
+            SetNum::Four => "4",
+            SetNum::Five => "5",
+            SetNum::Six => "6",
+        }
+    }
+}
+
+impl SetColor {
+    fn name(&self) -> &'static str {
+        match self {
+            SetColor::Purple => "purple",
+            SetColor::Red => "red",
+            SetColor::Green => "green",
+            SetColor::Black => "black",
+            SetColor::Brown => "brown",
+            SetColor::Blue => "blue",
+        }
+    }
+}
+
+impl Shape {
+    fn name(&self) -> &'static str {
+        match self {
+            Shape::Diamond => "diamond",
+            Shape::Pill => "pill",
+            Shape::Squiggle => "squiggle",
+            Shape::Heart => "heart",
+            Shape::Spade => "spade",
+            Shape::Club => "club",
+        }
+    }
+}
+
+impl Filling {
+    fn name(&self) -> &'static str {
+        match self {
+            Filling::Hollow => "hollow",
+            Filling::Solid => "solid",
+            Filling::HorizontalStriped => "striped",
+            Filling::DiagonalStriped => "diagonal-striped",
+            Filling::Checkerboard => "checkered",
+            Filling::VerticalStriped => "vertical-striped",
+        }
+    }
+}
+
+/// Implements `Display` as the name of the attribute, and `FromStr` as its inverse
+macro_rules! named_attribute {
+    ($attribute:ident, $expected:literal) => {
+        impl fmt::Display for $attribute {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl FromStr for $attribute {
+            type Err = ParseAttributeError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                (0..6)
+                    .filter_map($attribute::index)
+                    .find(|value| value.name().eq_ignore_ascii_case(s))
+                    .ok_or_else(|| ParseAttributeError::new($expected, s))
+            }
+        }
+    };
+}
+
+named_attribute!(SetNum, "number");
+named_attribute!(SetColor, "color");
+named_attribute!(Shape, "shape");
+named_attribute!(Filling, "filling");
+
+/// Written as number, color, filling and shape, e.g. "2 red striped hearts" or
+/// "1 green hollow diamond"
+impl fmt::Display for CardVisualAttr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = if self.num == SetNum::One { "" } else { "s" };
+        write!(
+            f,
+            "{} {} {} {}{}",
+            self.num, self.color, self.filling, self.shape, plural
+        )
+    }
+}
+
+/// Reads the notation of `Display`. Case is ignored, and so is whether the shape is plural.
+impl FromStr for CardVisualAttr {
+    type Err = ParseAttributeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (num, color, filling, shape) = match words[..] {
+            [num, color, filling, shape] => (num, color, filling, shape),
+            _ => return Err(ParseAttributeError::new("card", s)),
+        };
+
+        let shape = shape
+            .parse()
+            .or_else(|error| match shape.strip_suffix(['s', 'S']) {
+                Some(singular) => singular.parse().map_err(|_| error),
+                None => Err(error),
+            })?;
+        Ok(CardVisualAttr {
+            num: num.parse()?,
+            color: color.parse()?,
+            shape,
+            filling: filling.parse()?,
+        })
+    }
+}
@@ -1,6 +1,35 @@
-use crate::{Attributes, Filling, SetColor, SetNum, Shape};
+use crate::{Attributes, Filling, QuadAttributes, SetColor, SetNum, Shape};
 use rand::prelude::*;
 
+/// Which attributes of a deck are randomized, and values that are never picked for them. The
+/// attributes that are not randomized keep their standard values, excluded or not.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttributeRandomization {
+    pub numbers: bool,
+    pub colors: bool,
+    pub shapes: bool,
+    pub fillings: bool,
+    pub excluded_numbers: Vec<SetNum>,
+    pub excluded_colors: Vec<SetColor>,
+    pub excluded_shapes: Vec<Shape>,
+    pub excluded_fillings: Vec<Filling>,
+}
+
+impl AttributeRandomization {
+    /// Randomizes every attribute without excluding any value, like
+    /// `generate_random_attributes`
+    #[must_use]
+    pub fn all() -> Self {
+        Self {
+            numbers: true,
+            colors: true,
+            shapes: true,
+            fillings: true,
+            ..Self::default()
+        }
+    }
+}
+
 /// # Panics
 ///
 /// Will not actually panic
@@ -49,11 +78,21 @@ pub fn generate_standard_attributes() -> Attributes {
 /// Will not actually panic
 #[must_use]
 pub fn generate_random_attributes() -> Attributes {
-    let mut rng = thread_rng();
+    generate_random_attributes_with_rng(&mut thread_rng())
+}
+
+/// Same as `generate_random_attributes`, but draws from the supplied RNG, so a seeded
+/// RNG always produces the same attributes.
+///
+/// # Panics
+///
+/// Will not actually panic
+#[must_use]
+pub fn generate_random_attributes_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Attributes {
     let mut indices = [0, 1, 2, 3, 4, 5];
 
     // Randomizing SetNum
-    indices.shuffle(&mut rng);
+    indices.shuffle(rng);
     let set_nums: [SetNum; 3] = (0..3)
         .into_iter()
         .map(|i| SetNum::index(indices[i]).unwrap())
@@ -62,7 +101,7 @@ pub fn generate_random_attributes() -> Attributes {
         .unwrap();
 
     // Randomizing SetColor
-    indices.shuffle(&mut rng);
+    indices.shuffle(rng);
     let set_colors: [SetColor; 3] = (0..3)
         .into_iter()
         .map(|i| SetColor::index(indices[i]).unwrap())
@@ -71,7 +110,7 @@ pub fn generate_random_attributes() -> Attributes {
         .unwrap();
 
     // Randomizing Shape
-    indices.shuffle(&mut rng);
+    indices.shuffle(rng);
     let shapes: [Shape; 3] = (0..3)
         .into_iter()
         .map(|i| Shape::index(indices[i]).unwrap())
@@ -80,7 +119,7 @@ pub fn generate_random_attributes() -> Attributes {
         .unwrap();
 
     // Randomizing Filling
-    indices.shuffle(&mut rng);
+    indices.shuffle(rng);
     let fillings: [Filling; 3] = (0..3)
         .into_iter()
         .map(|i| Filling::index(indices[i]).unwrap())
@@ -95,3 +134,120 @@ pub fn generate_random_attributes() -> Attributes {
         fillings,
     }
 }
+
+/// Picks three values at random for the attributes `randomization` asks for, never picking an
+/// excluded value, and keeps the standard values of the others. Returns `None` if fewer than
+/// three values of a randomized attribute are left once the excluded ones are taken out.
+#[must_use]
+pub fn generate_partially_random_attributes(
+    randomization: &AttributeRandomization,
+) -> Option<Attributes> {
+    generate_partially_random_attributes_with_rng(randomization, &mut thread_rng())
+}
+
+/// Same as `generate_partially_random_attributes`, but draws from the supplied RNG.
+#[must_use]
+pub fn generate_partially_random_attributes_with_rng<R: Rng + ?Sized>(
+    randomization: &AttributeRandomization,
+    rng: &mut R,
+) -> Option<Attributes> {
+    let standard = generate_standard_attributes();
+    Some(Attributes {
+        numbers: pick_three(
+            standard.numbers,
+            randomization.numbers,
+            &randomization.excluded_numbers,
+            SetNum::index,
+            rng,
+        )?,
+        colors: pick_three(
+            standard.colors,
+            randomization.colors,
+            &randomization.excluded_colors,
+            SetColor::index,
+            rng,
+        )?,
+        shapes: pick_three(
+            standard.shapes,
+            randomization.shapes,
+            &randomization.excluded_shapes,
+            Shape::index,
+            rng,
+        )?,
+        fillings: pick_three(
+            standard.fillings,
+            randomization.fillings,
+            &randomization.excluded_fillings,
+            Filling::index,
+            rng,
+        )?,
+    })
+}
+
+/// Picks three of the values of an attribute that are not excluded if `randomize`, and returns
+/// the standard values otherwise
+fn pick_three<T: Copy + PartialEq, R: Rng + ?Sized>(
+    standard: [T; 3],
+    randomize: bool,
+    excluded: &[T],
+    index: fn(usize) -> Option<T>,
+    rng: &mut R,
+) -> Option<[T; 3]> {
+    if !randomize {
+        return Some(standard);
+    }
+
+    let mut values: Vec<T> = (0..6)
+        .filter_map(index)
+        .filter(|value| !excluded.contains(value))
+        .collect();
+    values.shuffle(rng);
+    values.truncate(3);
+    values.try_into().ok()
+}
+
+/// The first four values of every attribute, used for the standard deck of Quads.
+///
+/// # Panics
+///
+/// Will not actually panic
+#[must_use]
+pub fn generate_standard_quad_attributes() -> QuadAttributes {
+    QuadAttributes {
+        numbers: [0, 1, 2, 3].map(|i| SetNum::index(i).unwrap()),
+        colors: [0, 1, 2, 3].map(|i| SetColor::index(i).unwrap()),
+        shapes: [0, 1, 2, 3].map(|i| Shape::index(i).unwrap()),
+        fillings: [0, 1, 2, 3].map(|i| Filling::index(i).unwrap()),
+    }
+}
+
+/// Picks four of the six values of every attribute at random, for an evil deck of Quads.
+///
+/// # Panics
+///
+/// Will not actually panic
+#[must_use]
+pub fn generate_random_quad_attributes() -> QuadAttributes {
+    generate_random_quad_attributes_with_rng(&mut thread_rng())
+}
+
+/// Same as `generate_random_quad_attributes`, but draws from the supplied RNG.
+///
+/// # Panics
+///
+/// Will not actually panic
+#[must_use]
+pub fn generate_random_quad_attributes_with_rng<R: Rng + ?Sized>(rng: &mut R) -> QuadAttributes {
+    let mut pick_four = || {
+        let mut indices = [0, 1, 2, 3, 4, 5];
+        indices.shuffle(rng);
+        [indices[0], indices[1], indices[2], indices[3]]
+    };
+
+    QuadAttributes {
+        numbers: pick_four().map(|i| SetNum::index(i).unwrap()),
+        colors: pick_four().map(|i| SetColor::index(i).unwrap()),
+        shapes: pick_four().map(|i| Shape::index(i).unwrap()),
+        fillings: pick_four().map(|i| Filling::index(i).unwrap()),
+    }
+}
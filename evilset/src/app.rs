@@ -21,20 +21,26 @@ use instant::Instant;
 // Platform independent imports
 use crate::themes::AppTheme;
 use backend_interface as backend;
-use cardgen::CardVisualAttr;
+use cardgen::{AttributeRandomization, Attributes, CardVisualAttr};
 use eframe::{
     egui::{Button, FontId, ImageButton, Key, Layout, RichText},
     epaint::TextureHandle,
     epi,
 };
-use setengine::{CardCoordinates, Deck, GameDeck, PlayResponse};
+use setengine::{
+    Bot, BotMove, CardCoordinates, Deck, Explanation, GameDeck, GameRules, Hint, HintLevel,
+    PlayResponse, Puzzle, PuzzleDate, SkillModel, Solution,
+};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     time::Duration,
 };
 use utility_functions as util;
 
 const TIMES_TO_DISPLAY: usize = 15;
+const DAILY_PUZZLE_SETS: usize = 6;
+// Number of sets played between changes of the attributes in Chaos Set
+const CHAOS_INTERVAL: usize = 3;
 const APP_KEY: &str = "evilset_app";
 // const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -73,26 +79,197 @@ struct PersistentGameData {
     theme: AppTheme,
     // Keeps a track of TIMES_TO_DISPLAY best times in each category
     times: Times,
+    // How good the computer opponent is
+    #[serde(default)]
+    bot_skill: BotSkill,
+    // Time taken to solve the daily puzzle, for each day it was solved
+    #[serde(default)]
+    daily_results: BTreeMap<String, Duration>,
+    // Whether wrong selections are explained
+    #[serde(default)]
+    practice_mode: bool,
+    // Which attributes are randomized in Evil Set and Evil Ultra Set
+    #[serde(default)]
+    evil_level: EvilLevel,
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Default, serde::Deserialize, serde::Serialize)]
 struct Times {
     set_times: Vec<Duration>,
     evilset_times: Vec<Duration>,
     ultraset_times: Vec<Duration>,
     evilultraset_times: Vec<Duration>,
+    // Times of games without hints in the modes that have no field above
+    #[serde(default)]
+    other_times: BTreeMap<TimedMode, Vec<Duration>>,
+    // Times of games in which hints were used, with the most revealing hint used
+    #[serde(default)]
+    assisted_times: BTreeMap<TimedMode, Vec<(Duration, HintLevel)>>,
+}
+
+/// A mode with a table of best times, with the level the evil modes were played at
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize, serde::Serialize,
+)]
+enum TimedMode {
+    Set,
+    EvilSet(EvilLevel),
+    UltraSet,
+    EvilUltraSet(EvilLevel),
+    Chaos,
+}
+
+impl TimedMode {
+    fn title(&self) -> String {
+        let level = |level: &EvilLevel| match level {
+            EvilLevel::Full => "",
+            EvilLevel::Medium => "Medium ",
+            EvilLevel::Mild => "Mild ",
+        };
+        match self {
+            TimedMode::Set => "Set".to_string(),
+            TimedMode::EvilSet(evil_level) => format!("{}Evil Set", level(evil_level)),
+            TimedMode::UltraSet => "Ultra Set".to_string(),
+            TimedMode::EvilUltraSet(evil_level) => format!("{}Evil Ultra Set", level(evil_level)),
+            TimedMode::Chaos => "Chaos Set".to_string(),
+        }
+    }
+
+    fn color(&self, theme: &AppTheme) -> egui::Color32 {
+        match self {
+            TimedMode::Set | TimedMode::UltraSet => crate::themes::thematic_blue(theme),
+            TimedMode::EvilSet(_) | TimedMode::EvilUltraSet(_) | TimedMode::Chaos => {
+                crate::themes::thematic_red(theme)
+            }
+        }
+    }
+}
+
+impl Times {
+    /// Records the time of a game won in `mode`, with the assisted times if hints were used
+    fn record(&mut self, mode: TimedMode, hint_level: Option<HintLevel>, time: Duration) {
+        match hint_level {
+            None => util::record_time(self.unassisted_mut(mode), time),
+            Some(level) => {
+                let times = self.assisted_times.entry(mode).or_default();
+                util::record_time(times, (time, level))
+            }
+        }
+    }
+
+    /// The best times of games in `mode` in which no hint was used
+    fn unassisted(&self, mode: TimedMode) -> &[Duration] {
+        match mode {
+            TimedMode::Set => &self.set_times,
+            TimedMode::EvilSet(EvilLevel::Full) => &self.evilset_times,
+            TimedMode::UltraSet => &self.ultraset_times,
+            TimedMode::EvilUltraSet(EvilLevel::Full) => &self.evilultraset_times,
+            _ => self.other_times.get(&mode).map_or(&[], Vec::as_slice),
+        }
+    }
+
+    fn unassisted_mut(&mut self, mode: TimedMode) -> &mut Vec<Duration> {
+        match mode {
+            TimedMode::Set => &mut self.set_times,
+            TimedMode::EvilSet(EvilLevel::Full) => &mut self.evilset_times,
+            TimedMode::UltraSet => &mut self.ultraset_times,
+            TimedMode::EvilUltraSet(EvilLevel::Full) => &mut self.evilultraset_times,
+            _ => self.other_times.entry(mode).or_default(),
+        }
+    }
+
+    /// The best times of games in `mode` in which hints were used
+    fn assisted(&self, mode: TimedMode) -> &[(Duration, HintLevel)] {
+        self.assisted_times.get(&mode).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+enum BotSkill {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Default for BotSkill {
+    fn default() -> Self {
+        BotSkill::Intermediate
+    }
+}
+
+impl BotSkill {
+    fn model(&self) -> SkillModel {
+        match self {
+            BotSkill::Beginner => SkillModel::beginner(),
+            BotSkill::Intermediate => SkillModel::intermediate(),
+            BotSkill::Expert => SkillModel::expert(),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            BotSkill::Beginner => "Beginner",
+            BotSkill::Intermediate => "Intermediate",
+            BotSkill::Expert => "Expert",
+        }
+    }
+}
+
+/// How much of a deck is randomized in the evil modes, from plain Set to Evil Set
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize, serde::Serialize,
+)]
+enum EvilLevel {
+    /// Random shapes and fillings
+    Mild,
+    /// Random colors, shapes and fillings
+    Medium,
+    /// Every attribute random
+    Full,
+}
+
+impl Default for EvilLevel {
+    fn default() -> Self {
+        EvilLevel::Full
+    }
+}
+
+impl EvilLevel {
+    fn randomization(&self) -> AttributeRandomization {
+        match self {
+            EvilLevel::Mild => AttributeRandomization {
+                shapes: true,
+                fillings: true,
+                ..AttributeRandomization::default()
+            },
+            EvilLevel::Medium => AttributeRandomization {
+                colors: true,
+                shapes: true,
+                fillings: true,
+                ..AttributeRandomization::default()
+            },
+            EvilLevel::Full => AttributeRandomization::all(),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            EvilLevel::Mild => "Mild",
+            EvilLevel::Medium => "Medium",
+            EvilLevel::Full => "Full",
+        }
+    }
 }
 
 impl Default for PersistentGameData {
     fn default() -> Self {
         Self {
             theme: AppTheme::Light,
-            times: Times {
-                set_times: Vec::new(),
-                evilset_times: Vec::new(),
-                ultraset_times: Vec::new(),
-                evilultraset_times: Vec::new(),
-            },
+            times: Times::default(),
+            bot_skill: BotSkill::default(),
+            daily_results: BTreeMap::new(),
+            practice_mode: false,
+            evil_level: EvilLevel::default(),
         }
     }
 }
@@ -104,6 +281,9 @@ enum AppState {
     EvilSet,
     UltraSet,
     EvilUltraSet,
+    VsComputer,
+    DailyPuzzle,
+    Chaos,
 }
 
 struct ActiveGameData {
@@ -113,8 +293,53 @@ struct ActiveGameData {
     game_started: Option<Instant>,
     game_ended: Option<Instant>,
     prev_frame: Option<PlayResponse>,
-    asked_for_hint: bool,
+    // The most revealing hint asked for so far
+    hint_level: Option<HintLevel>,
+    // The last hint given, and the number of moves played when it was given
+    hint: Option<(Hint, usize)>,
+    // Why the last selection was not a valid play, until the next selection
+    explanation: Option<Explanation>,
     updated_times: bool,
+    opponent: Option<Opponent>,
+    puzzle: Option<DailyPuzzle>,
+    // The search for the best line of play from the start of the game, started when it ends,
+    // and whether the window telling the player about it is still open
+    best_line: Option<(render::Promise<Solution>, bool)>,
+}
+
+/// The computer player in a game against the computer, and the score
+struct Opponent {
+    bot: Bot,
+    // The claim the bot is working towards, the number of moves played when it was decided
+    // upon, and when it was decided upon
+    pending: Option<(BotMove, usize, Instant)>,
+    // Scores are signed, since every wrong claim of the bot costs it a point
+    human_score: i32,
+    bot_score: i32,
+    // Whether the last claim of the bot was right, if it made one
+    last_claim: Option<bool>,
+}
+
+impl Opponent {
+    fn new(skill: BotSkill) -> Self {
+        Self {
+            bot: Bot::new(skill.model()),
+            pending: None,
+            human_score: 0,
+            bot_score: 0,
+            last_claim: None,
+        }
+    }
+}
+
+/// The daily puzzle being solved, and the sets found so far
+struct DailyPuzzle {
+    date: PuzzleDate,
+    puzzle: Puzzle,
+    // Indices in puzzle.sets of the sets found, in the order they were found
+    found: Vec<usize>,
+    // Whether the last selection was a set that had already been found
+    found_again: bool,
 }
 
 pub(crate) type TextureMap = HashMap<(CardCoordinates, CardVisualAttr), TextureHandle>;
@@ -123,6 +348,9 @@ pub(crate) type TextureMap = HashMap<(CardCoordinates, CardVisualAttr), TextureH
 struct RenderingPromises {
     standard_deck: Option<render::Promise<TextureMap>>,
     randomized_deck: Option<render::Promise<TextureMap>>,
+    // In Chaos Set, the number of sets after which the attributes change, what they change to,
+    // and the cards drawn with them
+    next_mapping: Option<(usize, Attributes, render::Promise<TextureMap>)>,
 }
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
@@ -213,6 +441,9 @@ impl epi::App for EvilSetApp {
             AppState::EvilSet => self.play_evilset(ctx, frame),
             AppState::UltraSet => self.play_ultraset(ctx, frame),
             AppState::EvilUltraSet => self.play_evilultraset(ctx, frame),
+            AppState::VsComputer => self.play_vs_computer(ctx, frame),
+            AppState::DailyPuzzle => self.play_daily_puzzle(ctx, frame),
+            AppState::Chaos => self.play_chaos(ctx, frame),
             AppState::Help => self.show_help(ctx, frame),
         }
     }
@@ -226,12 +457,14 @@ impl EvilSetApp {
             app_state,
             previous_state,
             game_data,
-            background_rendering: _,
+            background_rendering,
         } = self;
 
         match previous_state {
             Some(_) => {
                 *game_data = None;
+                // The next cards of Chaos Set belong to the game that was left
+                background_rendering.next_mapping = None;
                 *previous_state = None;
             }
             None => {}
@@ -317,6 +550,14 @@ impl EvilSetApp {
                             ui.monospace(util::standard_format(*time));
                         }
                     });
+
+                    ui.separator();
+
+                    show_other_times(ui, persistent_data);
+
+                    ui.separator();
+
+                    show_assisted_times(ui, persistent_data);
                 });
 
                 ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
@@ -409,6 +650,76 @@ impl EvilSetApp {
                 {
                     *app_state = AppState::EvilUltraSet;
                 }
+
+                egui::ComboBox::from_label("Evil level")
+                    .selected_text(persistent_data.evil_level.name())
+                    .show_ui(ui, |ui| {
+                        for level in [EvilLevel::Mild, EvilLevel::Medium, EvilLevel::Full] {
+                            ui.selectable_value(
+                                &mut persistent_data.evil_level,
+                                level,
+                                level.name(),
+                            );
+                        }
+                    });
+
+                if ui
+                    .add(egui::Button::new(
+                        RichText::new("    Chaos Set     ")
+                            .font(FontId::proportional(23.0))
+                            .color(crate::themes::thematic_red(&persistent_data.theme)),
+                    ))
+                    .clicked()
+                {
+                    *app_state = AppState::Chaos;
+                }
+
+                ui.checkbox(
+                    &mut persistent_data.practice_mode,
+                    "Practice mode: explain wrong selections",
+                );
+
+                if ui
+                    .add(egui::Button::new(
+                        RichText::new("   vs Computer    ")
+                            .font(FontId::proportional(23.0))
+                            .color(crate::themes::thematic_blue(&persistent_data.theme)),
+                    ))
+                    .clicked()
+                {
+                    *app_state = AppState::VsComputer;
+                }
+
+                egui::ComboBox::from_label("Computer skill")
+                    .selected_text(persistent_data.bot_skill.name())
+                    .show_ui(ui, |ui| {
+                        for skill in [BotSkill::Beginner, BotSkill::Intermediate, BotSkill::Expert]
+                        {
+                            ui.selectable_value(
+                                &mut persistent_data.bot_skill,
+                                skill,
+                                skill.name(),
+                            );
+                        }
+                    });
+
+                if ui
+                    .add(egui::Button::new(
+                        RichText::new("  Daily puzzle   ")
+                            .font(FontId::proportional(23.0))
+                            .color(crate::themes::thematic_red(&persistent_data.theme)),
+                    ))
+                    .clicked()
+                {
+                    *app_state = AppState::DailyPuzzle;
+                }
+
+                let today = util::today().to_string();
+                let result = match persistent_data.daily_results.get(&today) {
+                    Some(time) => format!("Solved today in {}", util::standard_format(*time)),
+                    None => "Not solved today".to_string(),
+                };
+                ui.label(RichText::new(result).font(FontId::proportional(18.0)));
             })
         });
     }
@@ -502,6 +813,14 @@ impl EvilSetApp {
                             ui.monospace(util::standard_format(*time));
                         }
                     });
+
+                    ui.separator();
+
+                    show_other_times(ui, persistent_data);
+
+                    ui.separator();
+
+                    show_assisted_times(ui, persistent_data);
                 });
 
                 ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
@@ -573,18 +892,11 @@ impl EvilSetApp {
             let rendering_promise = background_rendering.standard_deck.as_mut().unwrap();
             match rendering_promise.ready() {
                 None => {
-                    egui::CentralPanel::default().show(ctx, |ui| {
-                        ui.vertical_centered_justified(|ui| {
-                            ui.label(
-                                RichText::new("Rendering cards").font(FontId::proportional(18.0)),
-                            );
-                            ui.add(egui::Spinner::new()); // still loading
-                        });
-                    });
+                    show_rendering_spinner(ctx);
                 }
                 Some(card_textures) => {
                     let deck = Deck::new_standard_deck();
-                    let active_deck = GameDeck::start_set_play(&deck);
+                    let active_deck = GameDeck::start_set_play(&deck).unwrap();
 
                     // For debugging purposes
                     // let mut active_deck = GameDeck::start_set_play(&deck);
@@ -597,8 +909,13 @@ impl EvilSetApp {
                         game_started: Some(Instant::now()),
                         game_ended: None,
                         prev_frame: None,
-                        asked_for_hint: false,
+                        hint_level: None,
+                        hint: None,
+                        explanation: None,
                         updated_times: false,
+                        opponent: None,
+                        puzzle: None,
+                        best_line: None,
                     });
                 }
             }
@@ -612,20 +929,27 @@ impl EvilSetApp {
             );
 
             let best_times_updated = game_data.as_ref().unwrap().updated_times;
-            if !game_still_running
-                && !best_times_updated
-                && !game_data.as_ref().unwrap().asked_for_hint
-            {
-                let times = &mut persistent_data.times.set_times;
+            if !game_still_running && !best_times_updated {
                 let elapsed_time = game_data.as_ref().unwrap().game_started.unwrap().elapsed()
                     - game_data.as_ref().unwrap().game_ended.unwrap().elapsed();
-                times.push(elapsed_time);
-                times.sort();
-                let end_index = std::cmp::min(TIMES_TO_DISPLAY, times.len());
-                *times = times[0..end_index].to_vec();
+                persistent_data.times.record(
+                    TimedMode::Set,
+                    game_data.as_ref().unwrap().hint_level,
+                    elapsed_time,
+                );
                 game_data.as_mut().unwrap().updated_times = true;
             }
 
+            // Explaining wrong selections in practice mode
+            show_explanation(
+                ctx,
+                persistent_data.practice_mode,
+                game_data.as_mut().unwrap(),
+            );
+
+            // Telling the player how few cards the game could have ended with
+            show_best_line(ctx, game_data.as_mut().unwrap());
+
             // Handling the keyboard events if nothing happened previous frame
             if game_data.as_ref().unwrap().prev_frame.is_none() && game_still_running {
                 keyboard_card_select(ctx, game_data.as_mut().unwrap());
@@ -633,118 +957,22 @@ impl EvilSetApp {
 
             egui::CentralPanel::default().show(ctx, |ui| {
                 // The central panel the region left after adding TopPanel's and SidePanel's
-
-                ui.horizontal(|ui| {
-                    ui.with_layout(Layout::left_to_right(), |ui| {
-                        let close_button = ui.add(Button::new(RichText::new("❌").size(25.0)));
-                        if close_button.clicked() {
-                            *app_state = AppState::Menu;
-                            *previous_state = Some(AppState::Set);
-                        }
-
-                        let hint_button = ui.add(Button::new(RichText::new("❓").size(25.0)));
-                        if hint_button.clicked() && game_still_running {
-                            backend::show_hint(game_data);
-                        }
-                    });
-
-                    ui.vertical_centered(|ui| {
-                        ui.heading(
-                            RichText::new("Set")
-                                .font(FontId::proportional(28.0))
-                                .color(crate::themes::thematic_blue(&persistent_data.theme)),
-                        );
-                    });
-
-                    ui.with_layout(Layout::right_to_left(), |ui| {
-                        ui.label(
-                            RichText::new(format!(
-                                "⏱ {}",
-                                util::standard_format(if game_still_running {
-                                    game_data.as_ref().unwrap().game_started.unwrap().elapsed()
-                                } else {
-                                    game_data.as_ref().unwrap().game_started.unwrap().elapsed()
-                                        - game_data.as_ref().unwrap().game_ended.unwrap().elapsed()
-                                })
-                            ))
-                            .font(FontId::proportional(28.0)),
-                        );
-
-                        let cards_left = game_data.as_ref().unwrap().active_deck.in_deck().len();
-
-                        ui.label(
-                            RichText::new(format!("{} cards left", cards_left))
-                                .font(FontId::proportional(23.0)),
-                        );
-                        ctx.request_repaint();
-                    });
-                });
-
-                ui.add_space(10.0);
-                ui.separator();
-                ui.add_space(20.0);
-
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    let ActiveGameData {
-                        active_deck,
-                        card_textures,
-                        selected,
-                        game_started: _,
-                        game_ended: _,
-                        prev_frame,
-                        asked_for_hint: _,
-                        updated_times: _,
-                    } = game_data.as_mut().unwrap();
-
-                    *ui.visuals_mut() =
-                        crate::themes::generate_card_theme(&persistent_data.theme, prev_frame);
-
-                    let available_width = ui.available_width();
-                    let available_height = ui.available_height();
-
-                    let card_textures = card_textures.as_ref().unwrap();
-
-                    ui.columns(3, |columns| {
-                        for (index, card) in active_deck.in_play().iter().enumerate() {
-                            let texture = card_textures.get(card).unwrap();
-
-                            let mut button = ImageButton::new(
-                                texture,
-                                util::scale_card(
-                                    available_width,
-                                    available_height,
-                                    active_deck.in_play().len() / 3,
-                                ),
-                            );
-
-                            if selected.contains(&index) {
-                                button = button.selected(true);
-                            }
-
-                            let response = &mut columns[index % 3].add(button);
-
-                            if response.clicked() && prev_frame.is_none() {
-                                backend::select_index(index, active_deck, selected);
-                            }
-                        }
-                    });
-
-                    if !game_still_running {
-                        ui.vertical_centered(|ui| {
-                            ui.label(RichText::new("Game over").font(FontId::proportional(23.0)));
-
-                            *ui.visuals_mut() =
-                                crate::themes::generate_base_theme(&persistent_data.theme);
-
-                            let close_button = ui
-                                .add(Button::new(RichText::new("Return to main menu").size(23.0)));
-                            if close_button.clicked() {
-                                *app_state = AppState::Menu;
-                                *previous_state = Some(AppState::Set);
-                            }
-                        });
-                    }
-                })
+                let theme = &persistent_data.theme;
+                let game_data = game_data.as_mut().unwrap();
+
+                let closed = show_game_header(
+                    ui,
+                    game_data,
+                    true,
+                    "Set",
+                    crate::themes::thematic_blue(theme),
+                    show_time_and_cards_left,
+                );
+                let returned = show_card_grid(ui, theme, game_data, "Game over");
+                if closed || returned {
+                    *app_state = AppState::Menu;
+                    *previous_state = Some(AppState::Set);
+                }
             });
         }
     }
@@ -759,8 +987,9 @@ impl EvilSetApp {
         } = self;
 
         if game_data.is_none() {
-            let deck = Deck::new_random_deck();
-            let active_deck = GameDeck::start_set_play(&deck);
+            let randomization = persistent_data.evil_level.randomization();
+            let deck = Deck::new_partially_random_deck(&randomization).unwrap();
+            let active_deck = GameDeck::start_set_play(&deck).unwrap();
 
             // For debugging purposes
             // let mut active_deck = GameDeck::start_set_play(&deck);
@@ -773,34 +1002,29 @@ impl EvilSetApp {
                 game_started: None,
                 game_ended: None,
                 prev_frame: None,
-                asked_for_hint: false,
+                hint_level: None,
+                hint: None,
+                explanation: None,
                 updated_times: false,
+                opponent: None,
+                puzzle: None,
+                best_line: None,
             });
 
             let rendering_promise = render::deck_texture_promise(deck, ctx);
             background_rendering.randomized_deck = Some(rendering_promise);
         } else {
             match &mut background_rendering.randomized_deck {
-                Some(rendering_promise) => {
-                    match rendering_promise.ready() {
-                        None => {
-                            egui::CentralPanel::default().show(ctx, |ui| {
-                                ui.vertical_centered_justified(|ui| {
-                                    ui.label(
-                                        RichText::new("Rendering cards")
-                                            .font(FontId::proportional(18.0)),
-                                    );
-                                    ui.add(egui::Spinner::new()); // still loading
-                                });
-                            });
-                        }
-                        Some(card_textures) => {
-                            game_data.as_mut().unwrap().card_textures = Some(card_textures.clone());
-                            game_data.as_mut().unwrap().game_started = Some(Instant::now());
-                            background_rendering.randomized_deck = None;
-                        }
+                Some(rendering_promise) => match rendering_promise.ready() {
+                    None => {
+                        show_rendering_spinner(ctx);
                     }
-                }
+                    Some(card_textures) => {
+                        game_data.as_mut().unwrap().card_textures = Some(card_textures.clone());
+                        game_data.as_mut().unwrap().game_started = Some(Instant::now());
+                        background_rendering.randomized_deck = None;
+                    }
+                },
                 None => {
                     // Checking if 3 cards have been selected, and if so, evaluating them for correctness
                     backend::evaluate_selection(game_data.as_mut().unwrap());
@@ -811,21 +1035,28 @@ impl EvilSetApp {
                     );
 
                     let best_times_updated = game_data.as_ref().unwrap().updated_times;
-                    if !game_still_running
-                        && !best_times_updated
-                        && !game_data.as_ref().unwrap().asked_for_hint
-                    {
-                        let times = &mut persistent_data.times.evilset_times;
+                    if !game_still_running && !best_times_updated {
                         let elapsed_time =
                             game_data.as_ref().unwrap().game_started.unwrap().elapsed()
                                 - game_data.as_ref().unwrap().game_ended.unwrap().elapsed();
-                        times.push(elapsed_time);
-                        times.sort();
-                        let end_index = std::cmp::min(TIMES_TO_DISPLAY, times.len());
-                        *times = times[0..end_index].to_vec();
+                        persistent_data.times.record(
+                            TimedMode::EvilSet(persistent_data.evil_level),
+                            game_data.as_ref().unwrap().hint_level,
+                            elapsed_time,
+                        );
                         game_data.as_mut().unwrap().updated_times = true;
                     }
 
+                    // Explaining wrong selections in practice mode
+                    show_explanation(
+                        ctx,
+                        persistent_data.practice_mode,
+                        game_data.as_mut().unwrap(),
+                    );
+
+                    // Telling the player how few cards the game could have ended with
+                    show_best_line(ctx, game_data.as_mut().unwrap());
+
                     // Handling the keyboard events if nothing happened previous frame
                     if game_data.as_ref().unwrap().prev_frame.is_none() && game_still_running {
                         keyboard_card_select(ctx, game_data.as_mut().unwrap());
@@ -833,141 +1064,159 @@ impl EvilSetApp {
 
                     egui::CentralPanel::default().show(ctx, |ui| {
                         // The central panel the region left after adding TopPanel's and SidePanel's
+                        let theme = &persistent_data.theme;
+                        let game_data = game_data.as_mut().unwrap();
+
+                        let closed = show_game_header(
+                            ui,
+                            game_data,
+                            true,
+                            "Evil Set",
+                            crate::themes::thematic_red(theme),
+                            show_time_and_cards_left,
+                        );
+                        let returned = show_card_grid(ui, theme, game_data, "Game over");
+                        if closed || returned {
+                            *app_state = AppState::Menu;
+                            *previous_state = Some(AppState::EvilSet);
+                        }
+                    });
+                }
+            }
+        }
+    }
 
-                        ui.horizontal(|ui| {
-                            ui.with_layout(Layout::left_to_right(), |ui| {
-                                let close_button =
-                                    ui.add(Button::new(RichText::new("❌").size(25.0)));
-                                if close_button.clicked() {
-                                    *app_state = AppState::Menu;
-                                    *previous_state = Some(AppState::EvilSet);
-                                }
-
-                                let hint_button =
-                                    ui.add(Button::new(RichText::new("❓").size(25.0)));
-                                if hint_button.clicked() && game_still_running {
-                                    backend::show_hint(game_data);
-                                }
-                            });
+    fn play_chaos(&mut self, ctx: &egui::Context, _frame: &epi::Frame) {
+        let Self {
+            persistent_data,
+            app_state,
+            previous_state,
+            game_data,
+            background_rendering,
+        } = self;
 
-                            ui.vertical_centered(|ui| {
-                                ui.heading(
-                                    RichText::new("Evil Set")
-                                        .font(FontId::proportional(28.0))
-                                        .color(crate::themes::thematic_red(&persistent_data.theme)),
-                                );
-                            });
+        if game_data.is_none() {
+            let deck = Deck::new_random_deck();
+            let active_deck = GameDeck::start_set_play(&deck).unwrap();
 
-                            ui.with_layout(Layout::right_to_left(), |ui| {
-                                ui.label(
-                                    RichText::new(format!(
-                                        "⏱ {}",
-                                        util::standard_format(if game_still_running {
-                                            game_data
-                                                .as_ref()
-                                                .unwrap()
-                                                .game_started
-                                                .unwrap()
-                                                .elapsed()
-                                        } else {
-                                            game_data
-                                                .as_ref()
-                                                .unwrap()
-                                                .game_started
-                                                .unwrap()
-                                                .elapsed()
-                                                - game_data
-                                                    .as_ref()
-                                                    .unwrap()
-                                                    .game_ended
-                                                    .unwrap()
-                                                    .elapsed()
-                                        })
-                                    ))
-                                    .font(FontId::proportional(28.0)),
-                                );
+            // For debugging purposes
+            // let mut active_deck = GameDeck::start_set_play(&deck);
+            // active_deck.in_deck_mut().clear();
 
-                                let cards_left =
-                                    game_data.as_ref().unwrap().active_deck.in_deck().len();
+            *game_data = Some(ActiveGameData {
+                active_deck,
+                card_textures: None,
+                selected: HashSet::new(),
+                game_started: None,
+                game_ended: None,
+                prev_frame: None,
+                hint_level: None,
+                hint: None,
+                explanation: None,
+                updated_times: false,
+                opponent: None,
+                puzzle: None,
+                best_line: None,
+            });
 
-                                ui.label(
-                                    RichText::new(format!("{} cards left", cards_left))
-                                        .font(FontId::proportional(23.0)),
-                                );
-                                ctx.request_repaint();
-                            });
-                        });
-
-                        ui.add_space(10.0);
-                        ui.separator();
-                        ui.add_space(20.0);
-
-                        egui::ScrollArea::vertical().show(ui, |ui| {
-                            let ActiveGameData {
-                                active_deck,
-                                card_textures,
-                                selected,
-                                game_started: _,
-                                game_ended: _,
-                                prev_frame,
-                                asked_for_hint: _,
-                                updated_times: _,
-                            } = game_data.as_mut().unwrap();
-
-                            *ui.visuals_mut() = crate::themes::generate_card_theme(
-                                &persistent_data.theme,
-                                prev_frame,
-                            );
+            let rendering_promise = render::deck_texture_promise(deck, ctx);
+            background_rendering.randomized_deck = Some(rendering_promise);
 
-                            let available_width = ui.available_width();
-                            let available_height = ui.available_height();
+            // The cards for the first change are drawn while the game is played
+            let next_deck = Deck::new_random_deck();
+            background_rendering.next_mapping = Some((
+                CHAOS_INTERVAL,
+                next_deck.attributes(),
+                render::deck_texture_promise(next_deck, ctx),
+            ));
+        } else {
+            match &mut background_rendering.randomized_deck {
+                Some(rendering_promise) => match rendering_promise.ready() {
+                    None => {
+                        show_rendering_spinner(ctx);
+                    }
+                    Some(card_textures) => {
+                        game_data.as_mut().unwrap().card_textures = Some(card_textures.clone());
+                        game_data.as_mut().unwrap().game_started = Some(Instant::now());
+                        background_rendering.randomized_deck = None;
+                    }
+                },
+                None => {
+                    // Checking if 3 cards have been selected, and if so, evaluating them for correctness
+                    backend::evaluate_selection(game_data.as_mut().unwrap());
 
-                            let card_textures = card_textures.as_ref().unwrap();
+                    // Changing the attributes once enough sets have been played
+                    change_chaos_mapping(
+                        ctx,
+                        game_data.as_mut().unwrap(),
+                        &mut background_rendering.next_mapping,
+                    );
 
-                            ui.columns(3, |columns| {
-                                for (index, card) in active_deck.in_play().iter().enumerate() {
-                                    let texture = card_textures.get(card).unwrap();
+                    let game_still_running = !matches!(
+                        game_data.as_ref().unwrap().prev_frame,
+                        Some(PlayResponse::GameOver)
+                    );
 
-                                    let mut button = ImageButton::new(
-                                        texture,
-                                        util::scale_card(
-                                            available_width,
-                                            available_height,
-                                            active_deck.in_play().len() / 3,
-                                        ),
-                                    );
+                    let best_times_updated = game_data.as_ref().unwrap().updated_times;
+                    if !game_still_running && !best_times_updated {
+                        let elapsed_time =
+                            game_data.as_ref().unwrap().game_started.unwrap().elapsed()
+                                - game_data.as_ref().unwrap().game_ended.unwrap().elapsed();
+                        persistent_data.times.record(
+                            TimedMode::Chaos,
+                            game_data.as_ref().unwrap().hint_level,
+                            elapsed_time,
+                        );
+                        game_data.as_mut().unwrap().updated_times = true;
+                    }
 
-                                    if selected.contains(&index) {
-                                        button = button.selected(true);
-                                    }
+                    // Explaining wrong selections in practice mode
+                    show_explanation(
+                        ctx,
+                        persistent_data.practice_mode,
+                        game_data.as_mut().unwrap(),
+                    );
 
-                                    let response = &mut columns[index % 3].add(button);
+                    // Telling the player how few cards the game could have ended with
+                    show_best_line(ctx, game_data.as_mut().unwrap());
 
-                                    if response.clicked() && prev_frame.is_none() {
-                                        backend::select_index(index, active_deck, selected);
-                                    }
-                                }
-                            });
+                    // Handling the keyboard events if nothing happened previous frame
+                    if game_data.as_ref().unwrap().prev_frame.is_none() && game_still_running {
+                        keyboard_card_select(ctx, game_data.as_mut().unwrap());
+                    }
 
-                            if !game_still_running {
-                                ui.vertical_centered(|ui| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        // The central panel the region left after adding TopPanel's and SidePanel's
+                        let theme = &persistent_data.theme;
+                        let next_mapping = &background_rendering.next_mapping;
+                        let game_data = game_data.as_mut().unwrap();
+
+                        let closed = show_game_header(
+                            ui,
+                            game_data,
+                            true,
+                            "Chaos Set",
+                            crate::themes::thematic_red(theme),
+                            |ui, game_data| {
+                                show_time_and_cards_left(ui, game_data);
+
+                                if let Some((switch_at, _, _)) = next_mapping {
                                     ui.label(
-                                        RichText::new("Game over").font(FontId::proportional(23.0)),
+                                        RichText::new(format!(
+                                            "{} sets to the next change",
+                                            switch_at
+                                                .saturating_sub(game_data.active_deck.plays_made())
+                                        ))
+                                        .font(FontId::proportional(23.0)),
                                     );
-
-                                    *ui.visuals_mut() =
-                                        crate::themes::generate_base_theme(&persistent_data.theme);
-
-                                    let close_button = ui.add(Button::new(
-                                        RichText::new("Return to main menu").size(23.0),
-                                    ));
-                                    if close_button.clicked() {
-                                        *app_state = AppState::Menu;
-                                        *previous_state = Some(AppState::EvilSet);
-                                    }
-                                });
-                            }
-                        })
+                                }
+                            },
+                        );
+                        let returned = show_card_grid(ui, theme, game_data, "Game over");
+                        if closed || returned {
+                            *app_state = AppState::Menu;
+                            *previous_state = Some(AppState::Chaos);
+                        }
                     });
                 }
             }
@@ -987,18 +1236,11 @@ impl EvilSetApp {
             let rendering_promise = background_rendering.standard_deck.as_mut().unwrap();
             match rendering_promise.ready() {
                 None => {
-                    egui::CentralPanel::default().show(ctx, |ui| {
-                        ui.vertical_centered_justified(|ui| {
-                            ui.label(
-                                RichText::new("Rendering cards").font(FontId::proportional(18.0)),
-                            );
-                            ui.add(egui::Spinner::new()); // still loading
-                        });
-                    });
+                    show_rendering_spinner(ctx);
                 }
                 Some(card_textures) => {
                     let deck = Deck::new_standard_deck();
-                    let active_deck = GameDeck::start_ultraset_play(&deck);
+                    let active_deck = GameDeck::start_ultraset_play(&deck).unwrap();
 
                     // For debugging purposes
                     // let mut active_deck = GameDeck::start_ultraset_play(&deck);
@@ -1011,8 +1253,13 @@ impl EvilSetApp {
                         game_started: Some(Instant::now()),
                         game_ended: None,
                         prev_frame: None,
-                        asked_for_hint: false,
+                        hint_level: None,
+                        hint: None,
+                        explanation: None,
                         updated_times: false,
+                        opponent: None,
+                        puzzle: None,
+                        best_line: None,
                     });
                 }
             }
@@ -1026,20 +1273,27 @@ impl EvilSetApp {
             );
 
             let best_times_updated = game_data.as_ref().unwrap().updated_times;
-            if !game_still_running
-                && !best_times_updated
-                && !game_data.as_ref().unwrap().asked_for_hint
-            {
-                let times = &mut persistent_data.times.ultraset_times;
+            if !game_still_running && !best_times_updated {
                 let elapsed_time = game_data.as_ref().unwrap().game_started.unwrap().elapsed()
                     - game_data.as_ref().unwrap().game_ended.unwrap().elapsed();
-                times.push(elapsed_time);
-                times.sort();
-                let end_index = std::cmp::min(TIMES_TO_DISPLAY, times.len());
-                *times = times[0..end_index].to_vec();
+                persistent_data.times.record(
+                    TimedMode::UltraSet,
+                    game_data.as_ref().unwrap().hint_level,
+                    elapsed_time,
+                );
                 game_data.as_mut().unwrap().updated_times = true;
             }
 
+            // Explaining wrong selections in practice mode
+            show_explanation(
+                ctx,
+                persistent_data.practice_mode,
+                game_data.as_mut().unwrap(),
+            );
+
+            // Telling the player how few cards the game could have ended with
+            show_best_line(ctx, game_data.as_mut().unwrap());
+
             // Handling the keyboard events if nothing happened previous frame
             if game_data.as_ref().unwrap().prev_frame.is_none() && game_still_running {
                 keyboard_card_select(ctx, game_data.as_mut().unwrap());
@@ -1047,123 +1301,235 @@ impl EvilSetApp {
 
             egui::CentralPanel::default().show(ctx, |ui| {
                 // The central panel the region left after adding TopPanel's and SidePanel's
+                let theme = &persistent_data.theme;
+                let game_data = game_data.as_mut().unwrap();
+
+                let closed = show_game_header(
+                    ui,
+                    game_data,
+                    true,
+                    "Ultra Set",
+                    crate::themes::thematic_blue(theme),
+                    show_time_and_cards_left,
+                );
+                let returned = show_card_grid(ui, theme, game_data, "Game over");
+                if closed || returned {
+                    *app_state = AppState::Menu;
+                    *previous_state = Some(AppState::UltraSet);
+                }
+            });
+        }
+    }
 
-                ui.horizontal(|ui| {
-                    ui.with_layout(Layout::left_to_right(), |ui| {
-                        let close_button = ui.add(Button::new(RichText::new("❌").size(25.0)));
-                        if close_button.clicked() {
-                            *app_state = AppState::Menu;
-                            *previous_state = Some(AppState::UltraSet);
-                        }
+    fn play_evilultraset(&mut self, ctx: &egui::Context, _frame: &epi::Frame) {
+        let Self {
+            persistent_data,
+            app_state,
+            previous_state,
+            game_data,
+            background_rendering,
+        } = self;
 
-                        let hint_button = ui.add(Button::new(RichText::new("❓").size(25.0)));
-                        if hint_button.clicked() && game_still_running {
-                            backend::show_hint(game_data);
-                        }
-                    });
+        if game_data.is_none() {
+            let randomization = persistent_data.evil_level.randomization();
+            let deck = Deck::new_partially_random_deck(&randomization).unwrap();
+            let active_deck = GameDeck::start_ultraset_play(&deck).unwrap();
 
-                    ui.vertical_centered(|ui| {
-                        ui.heading(
-                            RichText::new("Ultra Set")
-                                .font(FontId::proportional(28.0))
-                                .color(crate::themes::thematic_blue(&persistent_data.theme)),
-                        );
-                    });
+            // For debugging purposes
+            // let mut active_deck = GameDeck::start_set_play(&deck);
+            // active_deck.in_deck_mut().clear();
 
-                    ui.with_layout(Layout::right_to_left(), |ui| {
-                        ui.label(
-                            RichText::new(format!(
-                                "⏱ {}",
-                                util::standard_format(if game_still_running {
-                                    game_data.as_ref().unwrap().game_started.unwrap().elapsed()
-                                } else {
-                                    game_data.as_ref().unwrap().game_started.unwrap().elapsed()
-                                        - game_data.as_ref().unwrap().game_ended.unwrap().elapsed()
-                                })
-                            ))
-                            .font(FontId::proportional(28.0)),
+            *game_data = Some(ActiveGameData {
+                active_deck,
+                card_textures: None,
+                selected: HashSet::new(),
+                game_started: None,
+                game_ended: None,
+                prev_frame: None,
+                hint_level: None,
+                hint: None,
+                explanation: None,
+                updated_times: false,
+                opponent: None,
+                puzzle: None,
+                best_line: None,
+            });
+
+            let rendering_promise = render::deck_texture_promise(deck, ctx);
+            background_rendering.randomized_deck = Some(rendering_promise);
+        } else {
+            match &mut background_rendering.randomized_deck {
+                Some(rendering_promise) => match rendering_promise.ready() {
+                    None => {
+                        show_rendering_spinner(ctx);
+                    }
+                    Some(card_textures) => {
+                        game_data.as_mut().unwrap().card_textures = Some(card_textures.clone());
+                        game_data.as_mut().unwrap().game_started = Some(Instant::now());
+                        background_rendering.randomized_deck = None;
+                    }
+                },
+                None => {
+                    // Checking if 3 cards have been selected, and if so, evaluating them for correctness
+                    backend::evaluate_selection(game_data.as_mut().unwrap());
+
+                    let game_still_running = !matches!(
+                        game_data.as_ref().unwrap().prev_frame,
+                        Some(PlayResponse::GameOver)
+                    );
+
+                    let best_times_updated = game_data.as_ref().unwrap().updated_times;
+                    if !game_still_running && !best_times_updated {
+                        let elapsed_time =
+                            game_data.as_ref().unwrap().game_started.unwrap().elapsed()
+                                - game_data.as_ref().unwrap().game_ended.unwrap().elapsed();
+                        persistent_data.times.record(
+                            TimedMode::EvilUltraSet(persistent_data.evil_level),
+                            game_data.as_ref().unwrap().hint_level,
+                            elapsed_time,
                         );
+                        game_data.as_mut().unwrap().updated_times = true;
+                    }
+
+                    // Explaining wrong selections in practice mode
+                    show_explanation(
+                        ctx,
+                        persistent_data.practice_mode,
+                        game_data.as_mut().unwrap(),
+                    );
 
-                        let cards_left = game_data.as_ref().unwrap().active_deck.in_deck().len();
+                    // Telling the player how few cards the game could have ended with
+                    show_best_line(ctx, game_data.as_mut().unwrap());
 
-                        ui.label(
-                            RichText::new(format!("{} cards left", cards_left))
-                                .font(FontId::proportional(23.0)),
+                    // Handling the keyboard events if nothing happened previous frame
+                    if game_data.as_ref().unwrap().prev_frame.is_none() && game_still_running {
+                        keyboard_card_select(ctx, game_data.as_mut().unwrap());
+                    }
+
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        // The central panel the region left after adding TopPanel's and SidePanel's
+                        let theme = &persistent_data.theme;
+                        let game_data = game_data.as_mut().unwrap();
+
+                        let closed = show_game_header(
+                            ui,
+                            game_data,
+                            true,
+                            "Evil Ultra Set",
+                            crate::themes::thematic_red(theme),
+                            show_time_and_cards_left,
                         );
-                        ctx.request_repaint();
+                        let returned = show_card_grid(ui, theme, game_data, "Game over");
+                        if closed || returned {
+                            *app_state = AppState::Menu;
+                            *previous_state = Some(AppState::EvilUltraSet);
+                        }
                     });
-                });
+                }
+            }
+        }
+    }
+
+    fn play_vs_computer(&mut self, ctx: &egui::Context, _frame: &epi::Frame) {
+        let Self {
+            persistent_data,
+            app_state,
+            previous_state,
+            game_data,
+            background_rendering,
+        } = self;
 
-                ui.add_space(10.0);
-                ui.separator();
-                ui.add_space(20.0);
+        if game_data.is_none() {
+            let rendering_promise = background_rendering.standard_deck.as_mut().unwrap();
+            match rendering_promise.ready() {
+                None => {
+                    show_rendering_spinner(ctx);
+                }
+                Some(card_textures) => {
+                    let deck = Deck::new_standard_deck();
+                    let active_deck = GameDeck::start_set_play(&deck).unwrap();
 
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    let ActiveGameData {
+                    *game_data = Some(ActiveGameData {
                         active_deck,
-                        card_textures,
-                        selected,
-                        game_started: _,
-                        game_ended: _,
-                        prev_frame,
-                        asked_for_hint: _,
-                        updated_times: _,
-                    } = game_data.as_mut().unwrap();
-
-                    *ui.visuals_mut() =
-                        crate::themes::generate_card_theme(&persistent_data.theme, prev_frame);
-
-                    let available_width = ui.available_width();
-                    let available_height = ui.available_height();
-
-                    let card_textures = card_textures.as_ref().unwrap();
-
-                    ui.columns(3, |columns| {
-                        for (index, card) in active_deck.in_play().iter().enumerate() {
-                            let texture = card_textures.get(card).unwrap();
-
-                            let mut button = ImageButton::new(
-                                texture,
-                                util::scale_card(
-                                    available_width,
-                                    available_height,
-                                    active_deck.in_play().len() / 3,
-                                ),
-                            );
+                        card_textures: Some(card_textures.clone()),
+                        selected: HashSet::new(),
+                        game_started: Some(Instant::now()),
+                        game_ended: None,
+                        prev_frame: None,
+                        hint_level: None,
+                        hint: None,
+                        explanation: None,
+                        updated_times: false,
+                        opponent: Some(Opponent::new(persistent_data.bot_skill)),
+                        puzzle: None,
+                        best_line: None,
+                    });
+                }
+            }
+        } else {
+            // Checking if 3 cards have been selected, and if so, evaluating them for correctness
+            backend::evaluate_selection(game_data.as_mut().unwrap());
 
-                            if selected.contains(&index) {
-                                button = button.selected(true);
-                            }
+            // Letting the computer claim a set once it has found one
+            backend::bot_turn(game_data.as_mut().unwrap());
 
-                            let response = &mut columns[index % 3].add(button);
+            let game_still_running = !matches!(
+                game_data.as_ref().unwrap().prev_frame,
+                Some(PlayResponse::GameOver)
+            );
 
-                            if response.clicked() && prev_frame.is_none() {
-                                backend::select_index(index, active_deck, selected);
-                            }
-                        }
-                    });
+            // Handling the keyboard events if nothing happened previous frame
+            if game_data.as_ref().unwrap().prev_frame.is_none() && game_still_running {
+                keyboard_card_select(ctx, game_data.as_mut().unwrap());
+            }
 
-                    if !game_still_running {
-                        ui.vertical_centered(|ui| {
-                            ui.label(RichText::new("Game over").font(FontId::proportional(23.0)));
+            egui::CentralPanel::default().show(ctx, |ui| {
+                // The central panel the region left after adding TopPanel's and SidePanel's
+                let theme = &persistent_data.theme;
+                let game_data = game_data.as_mut().unwrap();
+                let title = format!("vs Computer ({})", persistent_data.bot_skill.name());
+                let opponent = game_data.opponent.as_ref().unwrap();
+                let game_over = match opponent.human_score.cmp(&opponent.bot_score) {
+                    std::cmp::Ordering::Greater => "Game over. You win!",
+                    std::cmp::Ordering::Less => "Game over. The computer wins!",
+                    std::cmp::Ordering::Equal => "Game over. It's a draw!",
+                };
+
+                let closed = show_game_header(
+                    ui,
+                    game_data,
+                    false,
+                    &title,
+                    crate::themes::thematic_blue(theme),
+                    |ui, game_data| {
+                        let opponent = game_data.opponent.as_ref().unwrap();
 
-                            *ui.visuals_mut() =
-                                crate::themes::generate_base_theme(&persistent_data.theme);
+                        ui.label(
+                            RichText::new(format!(
+                                "You {} : {} Computer",
+                                opponent.human_score, opponent.bot_score
+                            ))
+                            .font(FontId::proportional(28.0)),
+                        );
 
-                            let close_button = ui
-                                .add(Button::new(RichText::new("Return to main menu").size(23.0)));
-                            if close_button.clicked() {
-                                *app_state = AppState::Menu;
-                                *previous_state = Some(AppState::UltraSet);
-                            }
-                        });
-                    }
-                })
+                        let claim = match opponent.last_claim {
+                            Some(true) => "Computer found a set",
+                            Some(false) => "Computer made a mistake",
+                            None => "",
+                        };
+                        ui.label(RichText::new(claim).font(FontId::proportional(23.0)));
+                    },
+                );
+                let returned = show_card_grid(ui, theme, game_data, game_over);
+                if closed || returned {
+                    *app_state = AppState::Menu;
+                    *previous_state = Some(AppState::VsComputer);
+                }
             });
         }
     }
 
-    fn play_evilultraset(&mut self, ctx: &egui::Context, _frame: &epi::Frame) {
+    fn play_daily_puzzle(&mut self, ctx: &egui::Context, _frame: &epi::Frame) {
         let Self {
             persistent_data,
             app_state,
@@ -1173,12 +1539,17 @@ impl EvilSetApp {
         } = self;
 
         if game_data.is_none() {
-            let deck = Deck::new_random_deck();
-            let active_deck = GameDeck::start_ultraset_play(&deck);
-
-            // For debugging purposes
-            // let mut active_deck = GameDeck::start_set_play(&deck);
-            // active_deck.in_deck_mut().clear();
+            let date = util::today();
+            let puzzle = Puzzle::daily(date, DAILY_PUZZLE_SETS, true).unwrap();
+            let deck = Deck {
+                cards: puzzle.board.clone(),
+            };
+            // The cards stay on the board, so the game is only used to hold them
+            let rules = GameRules {
+                guarantee_move: false,
+                ..GameRules::set()
+            };
+            let active_deck = GameDeck::start_set_play_with_rules(&deck, rules).unwrap();
 
             *game_data = Some(ActiveGameData {
                 active_deck,
@@ -1187,57 +1558,54 @@ impl EvilSetApp {
                 game_started: None,
                 game_ended: None,
                 prev_frame: None,
-                asked_for_hint: false,
+                hint_level: None,
+                hint: None,
+                explanation: None,
                 updated_times: false,
+                opponent: None,
+                puzzle: Some(DailyPuzzle {
+                    date,
+                    puzzle,
+                    found: Vec::new(),
+                    found_again: false,
+                }),
+                best_line: None,
             });
 
             let rendering_promise = render::deck_texture_promise(deck, ctx);
             background_rendering.randomized_deck = Some(rendering_promise);
         } else {
             match &mut background_rendering.randomized_deck {
-                Some(rendering_promise) => {
-                    match rendering_promise.ready() {
-                        None => {
-                            egui::CentralPanel::default().show(ctx, |ui| {
-                                ui.vertical_centered_justified(|ui| {
-                                    ui.label(
-                                        RichText::new("Rendering cards")
-                                            .font(FontId::proportional(18.0)),
-                                    );
-                                    ui.add(egui::Spinner::new()); // still loading
-                                });
-                            });
-                        }
-                        Some(card_textures) => {
-                            game_data.as_mut().unwrap().card_textures = Some(card_textures.clone());
-                            game_data.as_mut().unwrap().game_started = Some(Instant::now());
-                            background_rendering.randomized_deck = None;
-                        }
+                Some(rendering_promise) => match rendering_promise.ready() {
+                    None => {
+                        show_rendering_spinner(ctx);
                     }
-                }
+                    Some(card_textures) => {
+                        game_data.as_mut().unwrap().card_textures = Some(card_textures.clone());
+                        game_data.as_mut().unwrap().game_started = Some(Instant::now());
+                        background_rendering.randomized_deck = None;
+                    }
+                },
                 None => {
-                    // Checking if 3 cards have been selected, and if so, evaluating them for correctness
-                    backend::evaluate_selection(game_data.as_mut().unwrap());
+                    // Checking if 3 cards have been selected, and if so, checking them against the sets on the board
+                    backend::evaluate_puzzle_selection(game_data.as_mut().unwrap());
 
                     let game_still_running = !matches!(
                         game_data.as_ref().unwrap().prev_frame,
                         Some(PlayResponse::GameOver)
                     );
 
-                    let best_times_updated = game_data.as_ref().unwrap().updated_times;
-                    if !game_still_running
-                        && !best_times_updated
-                        && !game_data.as_ref().unwrap().asked_for_hint
-                    {
-                        let times = &mut persistent_data.times.evilultraset_times;
-                        let elapsed_time =
-                            game_data.as_ref().unwrap().game_started.unwrap().elapsed()
-                                - game_data.as_ref().unwrap().game_ended.unwrap().elapsed();
-                        times.push(elapsed_time);
-                        times.sort();
-                        let end_index = std::cmp::min(TIMES_TO_DISPLAY, times.len());
-                        *times = times[0..end_index].to_vec();
-                        game_data.as_mut().unwrap().updated_times = true;
+                    // Only the first time the puzzle is solved on a given day counts
+                    if !game_still_running && !game_data.as_ref().unwrap().updated_times {
+                        let game_data = game_data.as_mut().unwrap();
+                        let elapsed_time = game_data.game_started.unwrap().elapsed()
+                            - game_data.game_ended.unwrap().elapsed();
+                        let date = game_data.puzzle.as_ref().unwrap().date.to_string();
+                        persistent_data
+                            .daily_results
+                            .entry(date)
+                            .or_insert(elapsed_time);
+                        game_data.updated_times = true;
                     }
 
                     // Handling the keyboard events if nothing happened previous frame
@@ -1247,148 +1615,396 @@ impl EvilSetApp {
 
                     egui::CentralPanel::default().show(ctx, |ui| {
                         // The central panel the region left after adding TopPanel's and SidePanel's
-
-                        ui.horizontal(|ui| {
-                            ui.with_layout(Layout::left_to_right(), |ui| {
-                                let close_button =
-                                    ui.add(Button::new(RichText::new("❌").size(25.0)));
-                                if close_button.clicked() {
-                                    *app_state = AppState::Menu;
-                                    *previous_state = Some(AppState::EvilUltraSet);
-                                }
-
-                                let hint_button =
-                                    ui.add(Button::new(RichText::new("❓").size(25.0)));
-                                if hint_button.clicked() && game_still_running {
-                                    backend::show_hint(game_data);
-                                }
-                            });
-
-                            ui.vertical_centered(|ui| {
-                                ui.heading(
-                                    RichText::new("Evil Ultra Set")
-                                        .font(FontId::proportional(28.0))
-                                        .color(crate::themes::thematic_red(&persistent_data.theme)),
+                        let theme = &persistent_data.theme;
+                        let game_data = game_data.as_mut().unwrap();
+                        let title =
+                            format!("Daily puzzle {}", game_data.puzzle.as_ref().unwrap().date);
+
+                        let closed = show_game_header(
+                            ui,
+                            game_data,
+                            false,
+                            &title,
+                            crate::themes::thematic_red(theme),
+                            |ui, game_data| {
+                                ui.label(
+                                    RichText::new(util::standard_format(game_time(game_data)))
+                                        .font(FontId::proportional(28.0)),
                                 );
-                            });
 
-                            ui.with_layout(Layout::right_to_left(), |ui| {
+                                let daily_puzzle = game_data.puzzle.as_ref().unwrap();
                                 ui.label(
                                     RichText::new(format!(
-                                        "⏱ {}",
-                                        util::standard_format(if game_still_running {
-                                            game_data
-                                                .as_ref()
-                                                .unwrap()
-                                                .game_started
-                                                .unwrap()
-                                                .elapsed()
-                                        } else {
-                                            game_data
-                                                .as_ref()
-                                                .unwrap()
-                                                .game_started
-                                                .unwrap()
-                                                .elapsed()
-                                                - game_data
-                                                    .as_ref()
-                                                    .unwrap()
-                                                    .game_ended
-                                                    .unwrap()
-                                                    .elapsed()
-                                        })
+                                        "Found {} of {}",
+                                        daily_puzzle.found.len(),
+                                        daily_puzzle.puzzle.sets.len()
                                     ))
-                                    .font(FontId::proportional(28.0)),
+                                    .font(FontId::proportional(23.0)),
                                 );
+                                if daily_puzzle.found_again {
+                                    ui.label(
+                                        RichText::new("Already found")
+                                            .font(FontId::proportional(23.0)),
+                                    );
+                                }
+                            },
+                        );
+                        let returned = show_card_grid(
+                            ui,
+                            theme,
+                            game_data,
+                            "Found every set. Come back tomorrow!",
+                        );
+                        if closed || returned {
+                            *app_state = AppState::Menu;
+                            *previous_state = Some(AppState::DailyPuzzle);
+                        }
+                    });
+                }
+            }
+        }
+    }
+}
 
-                                let cards_left =
-                                    game_data.as_ref().unwrap().active_deck.in_deck().len();
+/// Shows a spinner while the cards are drawn
+fn show_rendering_spinner(ctx: &egui::Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.vertical_centered_justified(|ui| {
+            ui.label(RichText::new("Rendering cards").font(FontId::proportional(18.0)));
+            ui.add(egui::Spinner::new()); // still loading
+        });
+    });
+}
 
-                                ui.label(
-                                    RichText::new(format!("{} cards left", cards_left))
-                                        .font(FontId::proportional(23.0)),
-                                );
-                                ctx.request_repaint();
-                            });
-                        });
-
-                        ui.add_space(10.0);
-                        ui.separator();
-                        ui.add_space(20.0);
-
-                        egui::ScrollArea::vertical().show(ui, |ui| {
-                            let ActiveGameData {
-                                active_deck,
-                                card_textures,
-                                selected,
-                                game_started: _,
-                                game_ended: _,
-                                prev_frame,
-                                asked_for_hint: _,
-                                updated_times: _,
-                            } = game_data.as_mut().unwrap();
-
-                            *ui.visuals_mut() = crate::themes::generate_card_theme(
-                                &persistent_data.theme,
-                                prev_frame,
-                            );
+/// Shows the row above the cards: a button to close the game, then the hint button and the hint
+/// given if `hints` is set, the title of the mode, and whatever `status` adds on the right.
+/// Returns whether the close button was clicked.
+fn show_game_header(
+    ui: &mut egui::Ui,
+    game_data: &mut ActiveGameData,
+    hints: bool,
+    title: &str,
+    color: egui::Color32,
+    status: impl FnOnce(&mut egui::Ui, &ActiveGameData),
+) -> bool {
+    let mut closed = false;
+
+    ui.horizontal(|ui| {
+        ui.with_layout(Layout::left_to_right(), |ui| {
+            let close_button = ui.add(Button::new(RichText::new("❌").size(25.0)));
+            closed = close_button.clicked();
+
+            if hints {
+                let game_still_running =
+                    !matches!(game_data.prev_frame, Some(PlayResponse::GameOver));
+                let hint_button = ui.add(Button::new(RichText::new("❓").size(25.0)));
+                if hint_button.clicked() && game_still_running {
+                    backend::show_hint(game_data);
+                }
+
+                if let Some(text) = backend::hint_text(game_data) {
+                    ui.label(RichText::new(text).font(FontId::proportional(18.0)));
+                }
+            }
+        });
+
+        ui.vertical_centered(|ui| {
+            ui.heading(
+                RichText::new(title)
+                    .font(FontId::proportional(28.0))
+                    .color(color),
+            );
+        });
 
-                            let available_width = ui.available_width();
-                            let available_height = ui.available_height();
+        ui.with_layout(Layout::right_to_left(), |ui| {
+            status(ui, game_data);
+            ui.ctx().request_repaint();
+        });
+    });
 
-                            let card_textures = card_textures.as_ref().unwrap();
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(20.0);
 
-                            ui.columns(3, |columns| {
-                                for (index, card) in active_deck.in_play().iter().enumerate() {
-                                    let texture = card_textures.get(card).unwrap();
+    closed
+}
 
-                                    let mut button = ImageButton::new(
-                                        texture,
-                                        util::scale_card(
-                                            available_width,
-                                            available_height,
-                                            active_deck.in_play().len() / 3,
-                                        ),
-                                    );
+/// Shows how long the game has lasted and how many cards are left in the deck, for the right of
+/// the header
+fn show_time_and_cards_left(ui: &mut egui::Ui, game_data: &ActiveGameData) {
+    ui.label(
+        RichText::new(format!("⏱ {}", util::standard_format(game_time(game_data))))
+            .font(FontId::proportional(28.0)),
+    );
 
-                                    if selected.contains(&index) {
-                                        button = button.selected(true);
-                                    }
+    let cards_left = game_data.active_deck.in_deck().len();
 
-                                    let response = &mut columns[index % 3].add(button);
+    ui.label(RichText::new(format!("{} cards left", cards_left)).font(FontId::proportional(23.0)));
+}
 
-                                    if response.clicked() && prev_frame.is_none() {
-                                        backend::select_index(index, active_deck, selected);
-                                    }
-                                }
-                            });
+/// How long the game has been going on for, or how long it lasted once it is over
+fn game_time(game_data: &ActiveGameData) -> Duration {
+    let since_start = game_data.game_started.unwrap().elapsed();
+    match game_data.game_ended {
+        Some(ended) => since_start - ended.elapsed(),
+        None => since_start,
+    }
+}
 
-                            if !game_still_running {
-                                ui.vertical_centered(|ui| {
-                                    ui.label(
-                                        RichText::new("Game over").font(FontId::proportional(23.0)),
-                                    );
+/// Shows the cards in play, which are selected by clicking on them, and once the game is over,
+/// the footer under them. Returns whether the button of the footer was clicked.
+fn show_card_grid(
+    ui: &mut egui::Ui,
+    theme: &AppTheme,
+    game_data: &mut ActiveGameData,
+    game_over: &str,
+) -> bool {
+    let mut returned = false;
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        let ActiveGameData {
+            active_deck,
+            card_textures,
+            selected,
+            prev_frame,
+            ..
+        } = game_data;
+
+        *ui.visuals_mut() = crate::themes::generate_card_theme(theme, prev_frame);
+
+        let available_width = ui.available_width();
+        let available_height = ui.available_height();
+
+        let card_textures = card_textures.as_ref().unwrap();
+
+        ui.columns(3, |columns| {
+            for (index, card) in active_deck.in_play().iter().enumerate() {
+                let texture = card_textures.get(card).unwrap();
+
+                let mut button = ImageButton::new(
+                    texture,
+                    util::scale_card(
+                        available_width,
+                        available_height,
+                        active_deck.in_play().len() / 3,
+                    ),
+                );
+
+                if selected.contains(&index) {
+                    button = button.selected(true);
+                }
 
-                                    *ui.visuals_mut() =
-                                        crate::themes::generate_base_theme(&persistent_data.theme);
-
-                                    let close_button = ui.add(Button::new(
-                                        RichText::new("Return to main menu").size(23.0),
-                                    ));
-                                    if close_button.clicked() {
-                                        *app_state = AppState::Menu;
-                                        *previous_state = Some(AppState::EvilUltraSet);
-                                    }
-                                });
-                            }
-                        })
-                    });
+                let response = &mut columns[index % 3].add(button);
+
+                if response.clicked() && prev_frame.is_none() {
+                    backend::select_index(index, active_deck, selected);
                 }
             }
+        });
+
+        if matches!(prev_frame, Some(PlayResponse::GameOver)) {
+            returned = show_game_over_footer(ui, theme, game_over);
         }
+    });
+
+    returned
+}
+
+/// Shows `message` and a button to return to the main menu at the end of a game. Returns whether
+/// the button was clicked.
+fn show_game_over_footer(ui: &mut egui::Ui, theme: &AppTheme, message: &str) -> bool {
+    let mut returned = false;
+
+    ui.vertical_centered(|ui| {
+        ui.label(RichText::new(message).font(FontId::proportional(23.0)));
+
+        *ui.visuals_mut() = crate::themes::generate_base_theme(theme);
+
+        let close_button = ui.add(Button::new(RichText::new("Return to main menu").size(23.0)));
+        returned = close_button.clicked();
+    });
+
+    returned
+}
+
+/// Lists the best times of the easier evil levels and of Chaos Set
+fn show_other_times(ui: &mut egui::Ui, persistent_data: &PersistentGameData) {
+    ui.vertical_centered(|ui| {
+        ui.heading("Other Evil Modes");
+    });
+
+    for mode in [
+        TimedMode::EvilSet(EvilLevel::Mild),
+        TimedMode::EvilSet(EvilLevel::Medium),
+        TimedMode::EvilUltraSet(EvilLevel::Mild),
+        TimedMode::EvilUltraSet(EvilLevel::Medium),
+        TimedMode::Chaos,
+    ] {
+        ui.separator();
+
+        ui.vertical(|ui| {
+            ui.label(
+                RichText::new(mode.title())
+                    .font(FontId::proportional(18.0))
+                    .color(mode.color(&persistent_data.theme)),
+            );
+            for time in persistent_data
+                .times
+                .unassisted(mode)
+                .iter()
+                .take(TIMES_TO_DISPLAY)
+            {
+                ui.monospace(util::standard_format(*time));
+            }
+        });
+    }
+}
+
+/// Lists the best times of games in which hints were used, with the most revealing hint used
+fn show_assisted_times(ui: &mut egui::Ui, persistent_data: &PersistentGameData) {
+    ui.vertical_centered(|ui| {
+        ui.heading("Assisted Times");
+    });
+
+    for mode in [
+        TimedMode::Set,
+        TimedMode::EvilSet(EvilLevel::Full),
+        TimedMode::UltraSet,
+        TimedMode::EvilUltraSet(EvilLevel::Full),
+        TimedMode::EvilSet(EvilLevel::Mild),
+        TimedMode::EvilSet(EvilLevel::Medium),
+        TimedMode::EvilUltraSet(EvilLevel::Mild),
+        TimedMode::EvilUltraSet(EvilLevel::Medium),
+        TimedMode::Chaos,
+    ] {
+        ui.separator();
+
+        ui.vertical(|ui| {
+            ui.label(
+                RichText::new(mode.title())
+                    .font(FontId::proportional(18.0))
+                    .color(mode.color(&persistent_data.theme)),
+            );
+            for (time, level) in persistent_data
+                .times
+                .assisted(mode)
+                .iter()
+                .take(TIMES_TO_DISPLAY)
+            {
+                let hint = match level {
+                    HintLevel::Card => "card",
+                    HintLevel::Attribute => "attribute",
+                    HintLevel::Cards => "cards",
+                };
+                ui.monospace(format!("{} {}", util::standard_format(*time), hint));
+            }
+        });
+    }
+}
+
+/// In practice mode, explains why the last selection was not a valid play, until the window is
+/// closed or another selection is made
+fn show_explanation(ctx: &egui::Context, practice_mode: bool, game_data: &mut ActiveGameData) {
+    let explanation = match &game_data.explanation {
+        Some(explanation) if practice_mode => explanation,
+        _ => return,
+    };
+
+    let title = match explanation {
+        Explanation::Set(_) => "Not a set",
+        Explanation::UltraSet(_) => "Not an ultraset",
+    };
+    let mut open = true;
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            for line in util::describe_explanation(explanation) {
+                ui.label(RichText::new(line).font(FontId::proportional(18.0)));
+            }
+        });
+
+    if !open {
+        game_data.explanation = None;
     }
 }
 
+/// Once the game is over, searches in the background for the line of play from the start of the
+/// game that leaves the fewest cards, and tells the player about it until the window is closed
+fn show_best_line(ctx: &egui::Context, game_data: &mut ActiveGameData) {
+    if !matches!(game_data.prev_frame, Some(PlayResponse::GameOver)) {
+        return;
+    }
+    if game_data.best_line.is_none() {
+        let promise = backend::best_line_promise(&game_data.active_deck);
+        game_data.best_line = Some((promise, true));
+    }
+
+    let (promise, open) = game_data.best_line.as_mut().unwrap();
+    if !*open {
+        return;
+    }
+
+    let active_deck = &game_data.active_deck;
+    let cards_left = active_deck.in_play().len() + active_deck.in_deck().len();
+    egui::Window::new("Game over")
+        .collapsible(false)
+        .resizable(false)
+        .open(open)
+        .show(ctx, |ui| match promise.ready() {
+            Some(solution) => {
+                ui.label(
+                    RichText::new(util::describe_best_line(cards_left, solution))
+                        .font(FontId::proportional(18.0)),
+                );
+            }
+            None => {
+                ui.vertical_centered_justified(|ui| {
+                    ui.label(
+                        RichText::new("Looking for the best line of play")
+                            .font(FontId::proportional(18.0)),
+                    );
+                    ui.add(egui::Spinner::new()); // still searching
+                });
+            }
+        });
+}
+
+/// Once enough sets have been played in Chaos Set, redraws the cards with the next attributes,
+/// and starts drawing the cards for the change after. Waits for the cards to be drawn, and for
+/// any selection being shown to be played, before changing anything.
+fn change_chaos_mapping(
+    ctx: &egui::Context,
+    game_data: &mut ActiveGameData,
+    next_mapping: &mut Option<(usize, Attributes, render::Promise<TextureMap>)>,
+) {
+    let (switch_at, attributes, rendering_promise) = match next_mapping {
+        Some(next) => next,
+        None => return,
+    };
+    if game_data.prev_frame.is_some() || game_data.active_deck.plays_made() < *switch_at {
+        return;
+    }
+    let card_textures = match rendering_promise.ready() {
+        Some(card_textures) => card_textures.clone(),
+        None => return,
+    };
+
+    let next_switch = *switch_at + CHAOS_INTERVAL;
+    game_data.active_deck.redraw_cards(attributes);
+    game_data.card_textures = Some(card_textures);
+    game_data.selected.clear();
+    game_data.hint = None;
+
+    let next_deck = Deck::new_random_deck();
+    *next_mapping = Some((
+        next_switch,
+        next_deck.attributes(),
+        render::deck_texture_promise(next_deck, ctx),
+    ));
+}
+
 fn keyboard_card_select(context: &egui::Context, game_data: &mut ActiveGameData) {
     let events = &context.input().events;
     let active_deck = &mut game_data.active_deck;
@@ -1456,6 +2072,13 @@ mod background_render {
         };
         Promise::spawn_thread("Background deck rendering", rendering_func)
     }
+
+    pub(super) fn spawn<T: Send + 'static>(
+        name: &str,
+        task: impl FnOnce() -> T + Send + 'static,
+    ) -> Promise<T> {
+        Promise::spawn_thread(name, task)
+    }
 }
 
 // When compiling for the web
@@ -1465,23 +2088,15 @@ mod foreground_render {
     use setengine::Deck;
 
     pub(super) struct Promise<T> {
-        deck: Deck,
-        context: egui::Context,
-        closure: fn(Deck, egui::Context) -> T,
+        task: Option<Box<dyn FnOnce() -> T>>,
         polled_once: bool,
         result: Option<T>,
     }
 
     impl<T> Promise<T> {
-        fn create(
-            deck: &Deck,
-            context: &egui::Context,
-            closure: fn(Deck, egui::Context) -> T,
-        ) -> Promise<T> {
+        fn create(task: impl FnOnce() -> T + 'static) -> Promise<T> {
             Promise {
-                deck: deck.clone(),
-                context: context.clone(),
-                closure,
+                task: Some(Box::new(task)),
                 polled_once: false,
                 result: None,
             }
@@ -1489,9 +2104,8 @@ mod foreground_render {
 
         pub(super) fn ready(&mut self) -> &Option<T> {
             if self.polled_once {
-                if self.result.is_none() {
-                    let function = self.closure;
-                    self.result = Some(function(self.deck.clone(), self.context.clone()));
+                if let Some(task) = self.task.take() {
+                    self.result = Some(task());
                 }
 
                 &self.result
@@ -1509,11 +2123,16 @@ mod foreground_render {
     }
 
     pub(super) fn deck_texture_promise(deck: Deck, ctx: &egui::Context) -> Promise<TextureMap> {
-        let rendering_func = |deck, context| {
+        let context = ctx.clone();
+
+        let rendering_func = move || {
             let filling_nodes = cardgen::generate_filling_nodes();
             generate_deck_textures(&deck, &filling_nodes, &context)
         };
+        Promise::create(rendering_func)
+    }
 
-        Promise::create(&deck, ctx, rendering_func)
+    pub(super) fn spawn<T: 'static>(_name: &str, task: impl FnOnce() -> T + 'static) -> Promise<T> {
+        Promise::create(task)
     }
 }
@@ -3,10 +3,14 @@ use std::thread::sleep;
 
 use super::GameDeck;
 use cardgen::CardVisualAttr;
-use setengine::{selection_is_set, selection_is_ultraset, CardCoordinates, PlayResponse};
+use setengine::{CardCoordinates, GameVariant, Hint, HintLevel, PlayResponse, Solution};
 use std::collections::HashSet;
 use std::time::Duration;
 
+/// Positions searched for the best line of play at the end of a game, before settling for the
+/// best line found so far
+const MAX_SOLVER_POSITIONS: usize = 100_000;
+
 #[cfg(target_arch = "wasm32")]
 fn sleep(duration: Duration) {
     use instant::Instant;
@@ -38,29 +42,87 @@ pub(super) fn select_index(
     }
 }
 
-pub(super) fn show_hint(game_data: &mut Option<super::ActiveGameData>) {
-    let active_deck = &game_data.as_ref().unwrap().active_deck;
-    let to_select = active_deck.get_hint();
+pub(super) fn show_hint(game_data: &mut super::ActiveGameData) {
+    let active_deck = &game_data.active_deck;
+
+    // Each hint about the same board gives away a little more than the last one
+    let board = active_deck.history().len();
+    let level = match &game_data.hint {
+        Some((hint, hint_board)) if *hint_board == board => match hint_level(hint).next() {
+            Some(level) => level,
+            None => return,
+        },
+        _ => HintLevel::Card,
+    };
+
+    let hint = match active_deck.get_graded_hint(level) {
+        Ok(hint) => hint,
+        Err(_) => return,
+    };
 
-    let selected = &mut game_data.as_mut().unwrap().selected;
+    let selected = &mut game_data.selected;
     selected.clear();
-    for i in to_select {
-        selected.insert(i);
+    match &hint {
+        Hint::Card(card) | Hint::Attribute { card, .. } => {
+            selected.insert(*card);
+        }
+        Hint::Cards(cards) => selected.extend(cards),
     }
 
-    game_data.as_mut().unwrap().asked_for_hint = true;
+    // The hint may give away more than asked for, when there is nothing useful to say at a level
+    game_data.hint_level = game_data.hint_level.max(Some(hint_level(&hint)));
+    game_data.hint = Some((hint, board));
+}
+
+/// Describes the last hint if it says more than which cards to select, and the board has not
+/// changed since
+pub(super) fn hint_text(game_data: &super::ActiveGameData) -> Option<String> {
+    match &game_data.hint {
+        Some((
+            Hint::Attribute {
+                card: _,
+                attribute,
+                all_same,
+            },
+            board,
+        )) if *board == game_data.active_deck.history().len() => {
+            let attribute = super::util::ATTRIBUTE_NAMES[*attribute];
+            if *all_same {
+                Some(format!("Same {} throughout", attribute))
+            } else {
+                Some(format!("All different {} throughout", attribute))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Starts searching for the line of play from the start of the game that leaves the fewest cards
+pub(super) fn best_line_promise(game: &GameDeck) -> super::render::Promise<Solution> {
+    let mut start = game.clone();
+    super::render::spawn("Best line search", move || {
+        while start.undo().is_ok() {}
+        start.solve(MAX_SOLVER_POSITIONS)
+    })
+}
+
+fn hint_level(hint: &Hint) -> HintLevel {
+    match hint {
+        Hint::Card(_) => HintLevel::Card,
+        Hint::Attribute { .. } => HintLevel::Attribute,
+        Hint::Cards(_) => HintLevel::Cards,
+    }
 }
 
 pub(super) fn evaluate_selection(game_data: &mut super::ActiveGameData) {
     let super::ActiveGameData {
         active_deck,
-        card_textures: _,
         selected,
-        game_started: _,
         game_ended,
         prev_frame,
-        asked_for_hint: _,
-        updated_times: _,
+        explanation,
+        opponent,
+        ..
     } = game_data;
 
     let num_selections = active_deck.selection_size();
@@ -80,7 +142,10 @@ pub(super) fn evaluate_selection(game_data: &mut super::ActiveGameData) {
             selected.clear();
 
             let result = active_deck.play_selection(selected_indices);
-            if let PlayResponse::GameOver = result {
+            if let (Ok(_), Some(opponent)) = (&result, opponent) {
+                opponent.human_score += 1;
+            }
+            if let Ok(PlayResponse::GameOver) = result {
                 *prev_frame = Some(PlayResponse::GameOver);
                 *game_ended = Some(super::Instant::now());
                 return;
@@ -95,15 +160,124 @@ pub(super) fn evaluate_selection(game_data: &mut super::ActiveGameData) {
             let selected_cards: Vec<(CardCoordinates, CardVisualAttr)> =
                 selected.iter().map(|p| active_deck.in_play()[*p]).collect();
 
-            let result = match active_deck {
-                GameDeck::Set(_) => selection_is_set(&selected_cards),
-                GameDeck::UltraSet(_) => selection_is_ultraset(&selected_cards),
-            };
-
-            if result {
+            if active_deck.variant().is_valid(&selected_cards) {
                 *prev_frame = Some(PlayResponse::ValidPlay);
+                *explanation = None;
             } else {
                 *prev_frame = Some(PlayResponse::InvalidPlay);
+                let selected_indices: Vec<usize> = selected.iter().copied().collect();
+                *explanation = active_deck.explain_selection(&selected_indices).ok();
+            }
+        }
+    }
+}
+
+pub(super) fn bot_turn(game_data: &mut super::ActiveGameData) {
+    let super::ActiveGameData {
+        active_deck,
+        selected,
+        game_ended,
+        prev_frame,
+        opponent,
+        ..
+    } = game_data;
+
+    let opponent = match opponent {
+        Some(opponent) => opponent,
+        None => return,
+    };
+    if prev_frame.is_some() {
+        return;
+    }
+
+    // Every play changes the board, so the length of the history identifies it
+    let board = active_deck.history().len();
+    if !matches!(opponent.pending, Some((_, pending_board, _)) if pending_board == board) {
+        opponent.pending = opponent
+            .bot
+            .decide(active_deck)
+            .map(|bot_move| (bot_move, board, super::Instant::now()));
+    }
+
+    let ready = match &opponent.pending {
+        Some((bot_move, _, decided_at)) => decided_at.elapsed() >= bot_move.time,
+        None => false,
+    };
+    if !ready {
+        return;
+    }
+
+    let (bot_move, _, _) = opponent.pending.take().unwrap();
+    if bot_move.mistake {
+        opponent.bot_score -= 1;
+        opponent.last_claim = Some(false);
+        return;
+    }
+
+    let response = match active_deck.play_selection(bot_move.selection) {
+        Ok(response) => response,
+        Err(_) => return,
+    };
+    selected.clear();
+    opponent.bot_score += 1;
+    opponent.last_claim = Some(true);
+    if response == PlayResponse::GameOver {
+        *prev_frame = Some(PlayResponse::GameOver);
+        *game_ended = Some(super::Instant::now());
+    }
+}
+
+pub(super) fn evaluate_puzzle_selection(game_data: &mut super::ActiveGameData) {
+    let super::ActiveGameData {
+        selected,
+        game_ended,
+        prev_frame,
+        puzzle,
+        ..
+    } = game_data;
+
+    let daily_puzzle = match puzzle {
+        Some(daily_puzzle) => daily_puzzle,
+        None => return,
+    };
+
+    if selected.len() != 3 {
+        return;
+    }
+
+    if prev_frame.is_some() {
+        sleep(Duration::from_millis(200));
+    }
+
+    match prev_frame {
+        Some(PlayResponse::GameOver) => {}
+        Some(_) => {
+            // The cards stay on the board, found or not
+            selected.clear();
+            *prev_frame = None;
+        }
+        None => {
+            let selected_indices: Vec<usize> = selected.iter().copied().collect();
+
+            match daily_puzzle.puzzle.find(&selected_indices) {
+                Some(index) if !daily_puzzle.found.contains(&index) => {
+                    daily_puzzle.found.push(index);
+                    daily_puzzle.found_again = false;
+                    if daily_puzzle.found.len() == daily_puzzle.puzzle.sets.len() {
+                        *prev_frame = Some(PlayResponse::GameOver);
+                        *game_ended = Some(super::Instant::now());
+                    } else {
+                        *prev_frame = Some(PlayResponse::ValidPlay);
+                    }
+                }
+                Some(_) => {
+                    daily_puzzle.found_again = true;
+                    *prev_frame = Some(PlayResponse::InvalidPlay);
+                }
+                None => {
+                    daily_puzzle.found_again = false;
+                    *prev_frame = Some(PlayResponse::InvalidPlay);
+                }
             }
         }
     }
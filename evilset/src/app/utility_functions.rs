@@ -1,5 +1,15 @@
+use setengine::{AttributePattern, Explanation, PuzzleDate, Solution};
 use std::time::Duration;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+#[cfg(target_arch = "wasm32")]
+use instant::SystemTime;
+
+/// What each attribute of the coordinates of a card is drawn as
+pub(super) const ATTRIBUTE_NAMES: [&str; 4] = ["number", "color", "shape", "filling"];
+
 // TODO: Also have a minimum height
 pub(super) fn scale_card(frame_width: f32, frame_height: f32, rows: usize) -> (f32, f32) {
     let scaling_with_width = {
@@ -22,9 +32,85 @@ pub(super) fn scale_card(frame_width: f32, frame_height: f32, rows: usize) -> (f
     }
 }
 
+/// Adds a time to a list of best times, keeping the best TIMES_TO_DISPLAY of them
+pub(super) fn record_time<T: Ord>(times: &mut Vec<T>, time: T) {
+    times.push(time);
+    times.sort();
+    times.truncate(super::TIMES_TO_DISPLAY);
+}
+
 pub(super) fn standard_format(duration: Duration) -> String {
     let seconds = duration.as_secs() % 60;
     let minutes = (duration.as_secs() / 60) % 60;
 
     format!("{:02}:{:02}", minutes, seconds)
 }
+
+/// The current day in UTC, which picks the daily puzzle
+pub(super) fn today() -> PuzzleDate {
+    let since_epoch = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    PuzzleDate::from_days_since_epoch((since_epoch.as_secs() / 86_400) as i64)
+}
+
+/// Describes an explanation line by line. Cards are numbered in the order they appear on the
+/// board.
+pub(super) fn describe_explanation(explanation: &Explanation) -> Vec<String> {
+    match explanation {
+        Explanation::Set(patterns) => patterns
+            .iter()
+            .zip(ATTRIBUTE_NAMES)
+            .map(|(pattern, name)| match pattern {
+                AttributePattern::AllSame => format!("✔ {}: all the same", name),
+                AttributePattern::AllDifferent => format!("✔ {}: all different", name),
+                AttributePattern::Broken => format!("❌ {}: two the same, one different", name),
+            })
+            .collect(),
+        Explanation::UltraSet(pairings) => pairings
+            .iter()
+            .map(|pairing| {
+                let [[a, b], [c, d]] = pairing.pairs;
+                let mismatched: Vec<&str> = pairing
+                    .mismatched
+                    .iter()
+                    .map(|&attribute| ATTRIBUTE_NAMES[attribute])
+                    .collect();
+                format!(
+                    "❌ cards {} & {} and {} & {} are completed by cards differing in {}",
+                    a + 1,
+                    b + 1,
+                    c + 1,
+                    d + 1,
+                    mismatched.join(", ")
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Compares the cards left at the end of a game with the fewest the game could have ended with
+pub(super) fn describe_best_line(cards_left: usize, solution: &Solution) -> String {
+    let cards = |count: usize| match count {
+        1 => "1 card".to_string(),
+        _ => format!("{} cards", count),
+    };
+
+    if solution.cards_left < cards_left {
+        let best = match solution.cards_left {
+            0 => "every card could have been played".to_string(),
+            best => format!("the game could have ended with {} left", cards(best)),
+        };
+        format!("You ended with {} left, but {}.", cards(cards_left), best)
+    } else if solution.optimal {
+        format!(
+            "You ended with {} left, as few as possible.",
+            cards(cards_left)
+        )
+    } else {
+        format!(
+            "You ended with {} left, and no better line of play was found.",
+            cards(cards_left)
+        )
+    }
+}
@@ -2,12 +2,23 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use setengine::*;
 
 fn detect_set(n: usize) {
-    let deck = Deck::new_standard_deck();
+    let deck: Deck = Deck::new_standard_deck();
     let cards = &deck.cards[0..n];
     selection_contains_set(cards);
 }
 fn detect_ultraset(n: usize) {
-    let deck = Deck::new_standard_deck();
+    let deck: Deck = Deck::new_standard_deck();
+    let cards = &deck.cards[0..n];
+    selection_contains_ultraset(cards);
+}
+
+fn detect_set_five_attributes(n: usize) {
+    let deck: Deck<5> = Deck::new_standard_deck();
+    let cards = &deck.cards[0..n];
+    selection_contains_set(cards);
+}
+fn detect_ultraset_five_attributes(n: usize) {
+    let deck: Deck<5> = Deck::new_standard_deck();
     let cards = &deck.cards[0..n];
     selection_contains_ultraset(cards);
 }
@@ -44,6 +55,14 @@ fn generalized_set_detection_benchmark(c: &mut Criterion) {
     c.bench_function("Detect ultraset in 21 random cards", |b| {
         b.iter(|| detect_ultraset(21))
     });
+
+    c.bench_function("Detect set in 30 random five-attribute cards", |b| {
+        b.iter(|| detect_set_five_attributes(30))
+    });
+
+    c.bench_function("Detect ultraset in 30 random five-attribute cards", |b| {
+        b.iter(|| detect_ultraset_five_attributes(30))
+    });
 }
 
 criterion_group!(benches, generalized_set_detection_benchmark);
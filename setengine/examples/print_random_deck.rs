@@ -1,7 +1,7 @@
 use setengine::*;
 
 fn main() {
-    let deck = Deck::new_random_deck();
+    let deck: Deck = Deck::new_random_deck();
     for p in deck.cards {
         println!("{:?}", p);
     }
@@ -0,0 +1,273 @@
+use crate::{CardCoordinates, Puzzle};
+use itertools::Itertools;
+use rand::prelude::*;
+
+/// A map of F_3^N to itself that sends sets to sets: the attributes are permuted, the values of
+/// each attribute are relabelled, and the result is translated. A card `x` is sent to the card
+/// whose `i`th attribute is `scaling[i] * x[permutation[i]] + translation[i]` mod 3.
+///
+/// These are the affine maps that keep attributes apart. Every other affine map, such as one
+/// that sends the number of a card to the sum of its number and color, also sends sets to
+/// sets, but there are far too many of them to search through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AffineTransform<const N: usize = 4> {
+    permutation: [usize; N],
+    scaling: [u8; N],
+    translation: CardCoordinates<N>,
+}
+
+impl<const N: usize> AffineTransform<N> {
+    /// Creates a transform from the attribute each attribute is taken from, the factor (1 or
+    /// 2) each attribute is multiplied by, and the translation added at the end. Panics if
+    /// `permutation` is not a permutation of `0..N`, or if a factor is 0 mod 3.
+    pub fn new(permutation: [usize; N], scaling: [u8; N], translation: CardCoordinates<N>) -> Self {
+        assert!(
+            permutation.iter().sorted().copied().eq(0..N),
+            "not a permutation of the attributes"
+        );
+        assert!(
+            scaling.iter().all(|factor| factor % 3 != 0),
+            "attributes can only be multiplied by 1 or 2"
+        );
+        Self {
+            permutation,
+            scaling: scaling.map(|factor| factor % 3),
+            translation,
+        }
+    }
+
+    /// The transform that leaves every card alone
+    pub fn identity() -> Self {
+        Self::new(std::array::from_fn(|i| i), [1; N], CardCoordinates::zero())
+    }
+
+    /// Adds `translation` to every card
+    pub fn translation(translation: CardCoordinates<N>) -> Self {
+        Self {
+            translation,
+            ..Self::identity()
+        }
+    }
+
+    /// Draws a transform uniformly at random
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut permutation: [usize; N] = std::array::from_fn(|i| i);
+        permutation.shuffle(rng);
+        Self {
+            permutation,
+            scaling: std::array::from_fn(|_| rng.gen_range(1..=2)),
+            translation: CardCoordinates::from_index(
+                rng.gen_range(0..CardCoordinates::<N>::DECK_SIZE),
+            ),
+        }
+    }
+
+    /// Iterates over every transform: `N! * 2^N * 3^N` of them, 31104 for the standard deck.
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::linear_parts().flat_map(|linear| {
+            CardCoordinates::all().map(move |translation| Self {
+                translation,
+                ..linear
+            })
+        })
+    }
+
+    /// Iterates over every transform without a translation
+    fn linear_parts() -> impl Iterator<Item = Self> {
+        (0..N).permutations(N).flat_map(|permutation| {
+            let permutation: [usize; N] = permutation.try_into().unwrap();
+            (0..1usize << N).map(move |signs| Self {
+                permutation,
+                scaling: std::array::from_fn(|i| 1 + ((signs >> i) & 1) as u8),
+                translation: CardCoordinates::zero(),
+            })
+        })
+    }
+
+    /// Returns the image of `card`
+    pub fn apply(&self, card: CardCoordinates<N>) -> CardCoordinates<N> {
+        let coords = card.coords();
+        let linear = CardCoordinates::from_array(std::array::from_fn(|i| {
+            self.scaling[i] * coords[self.permutation[i]]
+        }));
+        linear + self.translation
+    }
+
+    /// Returns the transform that applies `self`, and then `next`
+    #[must_use]
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            permutation: std::array::from_fn(|i| self.permutation[next.permutation[i]]),
+            scaling: std::array::from_fn(|i| {
+                (next.scaling[i] * self.scaling[next.permutation[i]]) % 3
+            }),
+            translation: next.apply(self.translation),
+        }
+    }
+
+    /// Returns the transform that undoes `self`
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        let mut inverse = Self::identity();
+        for i in 0..N {
+            // Since 2 * 2 = 1 mod 3, every factor is its own inverse
+            inverse.permutation[self.permutation[i]] = i;
+            inverse.scaling[self.permutation[i]] = self.scaling[i];
+        }
+        inverse.translation = CardCoordinates::zero() - inverse.apply(self.translation);
+        inverse
+    }
+}
+
+impl<const N: usize> CardCoordinates<N> {
+    /// Returns the image of this card under `transform`
+    #[must_use]
+    pub fn transformed(self, transform: &AffineTransform<N>) -> Self {
+        transform.apply(self)
+    }
+}
+
+/// Returns the canonical form of a board: the same cards for every board that can be sent to it
+/// by an `AffineTransform`, and different cards otherwise. The cards are sorted by index, so
+/// that the order of the board does not matter either, and the board is assumed to have no
+/// repeated cards.
+pub fn canonical_board<const N: usize>(cards: &[CardCoordinates<N>]) -> Vec<CardCoordinates<N>> {
+    let transform = canonical_transform(cards);
+    let mut canonical: Vec<_> = cards.iter().map(|&card| transform.apply(card)).collect();
+    canonical.sort_unstable_by_key(CardCoordinates::index);
+    canonical
+}
+
+/// Returns a transform that sends `cards` to their canonical form, see `canonical_board`.
+pub fn canonical_transform<const N: usize>(cards: &[CardCoordinates<N>]) -> AffineTransform<N> {
+    let mut best: Option<(Vec<usize>, AffineTransform<N>)> = None;
+
+    // The canonical form is the image whose sorted indices are smallest. That image always
+    // contains the zero card, so the only translations worth trying are those sending one of
+    // the cards to zero.
+    for linear in AffineTransform::<N>::linear_parts() {
+        let images: Vec<_> = cards.iter().map(|&card| linear.apply(card)).collect();
+        for &origin in &images {
+            let mut indices: Vec<usize> = images
+                .iter()
+                .map(|&image| (image - origin).index())
+                .collect();
+            indices.sort_unstable();
+
+            if !matches!(&best, Some((smallest, _)) if *smallest <= indices) {
+                let transform = AffineTransform {
+                    translation: CardCoordinates::zero() - origin,
+                    ..linear
+                };
+                best = Some((indices, transform));
+            }
+        }
+    }
+
+    best.map_or_else(AffineTransform::identity, |(_, transform)| transform)
+}
+
+impl<const N: usize> Puzzle<N> {
+    /// Returns the canonical form of the board of the puzzle, under which puzzles with the same
+    /// structure are the same. See `canonical_board`.
+    pub fn canonical_board(&self) -> Vec<CardCoordinates<N>> {
+        let cards: Vec<_> = self.board.iter().map(|card| card.0).collect();
+        canonical_board(&cards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_board, canonical_transform, AffineTransform};
+    use crate::cards::is_set;
+    use crate::{CardCoordinates, Deck};
+    use itertools::Itertools;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn random_transform(seed: u64) -> AffineTransform {
+        AffineTransform::random(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    fn board(seed: u64, size: usize) -> Vec<CardCoordinates> {
+        Deck::<4>::standard_from_seed(seed).cards[..size]
+            .iter()
+            .map(|card| card.0)
+            .collect()
+    }
+
+    #[quickcheck]
+    fn transforms_send_sets_to_sets(seed: u64) -> bool {
+        let transform = random_transform(seed);
+        board(seed, 12)
+            .into_iter()
+            .tuple_combinations()
+            .all(|(a, b, c)| {
+                is_set(a, b, c)
+                    == is_set(
+                        a.transformed(&transform),
+                        b.transformed(&transform),
+                        c.transformed(&transform),
+                    )
+            })
+    }
+
+    #[quickcheck]
+    fn composition_and_inverse_agree_with_apply(seed: u64) -> bool {
+        let first = random_transform(seed);
+        let second = random_transform(seed.wrapping_add(1));
+        let composite = first.then(&second);
+
+        CardCoordinates::<4>::all().all(|card| {
+            composite.apply(card) == second.apply(first.apply(card))
+                && first.inverse().apply(first.apply(card)) == card
+        }) && first.then(&first.inverse()) == AffineTransform::identity()
+    }
+
+    #[quickcheck]
+    fn canonical_boards_ignore_transforms(seed: u64, size: u8) -> bool {
+        let cards = board(seed, (size % 13) as usize);
+        let transformed: Vec<_> = cards
+            .iter()
+            .rev()
+            .map(|card| card.transformed(&random_transform(seed)))
+            .collect();
+
+        let canonical = canonical_board(&cards);
+        let mut image: Vec<_> = cards
+            .iter()
+            .map(|card| card.transformed(&canonical_transform(&cards)))
+            .collect();
+        image.sort_unstable_by_key(CardCoordinates::index);
+
+        canonical == canonical_board(&transformed) && canonical == image
+    }
+
+    #[test]
+    fn boards_with_different_structure_differ() {
+        let all_transforms = AffineTransform::<3>::all().count();
+        assert_eq!(all_transforms, 6 * 8 * 27);
+        assert_eq!(AffineTransform::<4>::all().count(), 31104);
+
+        // A set and three cards that are not a set are not equivalent
+        let set = [
+            CardCoordinates::new(0, 0, 0, 0),
+            CardCoordinates::new(1, 1, 1, 1),
+            CardCoordinates::new(2, 2, 2, 2),
+        ];
+        let not_set = [
+            CardCoordinates::new(0, 0, 0, 0),
+            CardCoordinates::new(1, 1, 1, 1),
+            CardCoordinates::new(2, 2, 2, 1),
+        ];
+        assert_ne!(canonical_board(&set), canonical_board(&not_set));
+
+        // Every set is equivalent to every other set that differs on as many attributes
+        let other_set = [
+            CardCoordinates::new(2, 1, 0, 0),
+            CardCoordinates::new(0, 2, 1, 1),
+            CardCoordinates::new(1, 0, 2, 2),
+        ];
+        assert_eq!(canonical_board(&set), canonical_board(&other_set));
+    }
+}
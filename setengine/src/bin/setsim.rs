@@ -0,0 +1,152 @@
+//! Prints statistics from simulated games of Set or UltraSet.
+//!
+//! Usage: `setsim [set|ultraset] [--games N] [--seed S] [--policy first|random]
+//! [--attributes 3|4|5] [--csv]`
+
+use setengine::sim::{simulate, Policy, Statistics};
+use setengine::SetVariant;
+use std::process::exit;
+
+const USAGE: &str = "usage: setsim [set|ultraset] [--games N] [--seed S] \
+[--policy first|random] [--attributes 3|4|5] [--csv]";
+
+struct Options {
+    ultraset: bool,
+    games: usize,
+    seed: u64,
+    policy: Policy,
+    attributes: usize,
+    csv: bool,
+}
+
+fn main() {
+    let options = parse_options(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}\n{}", message, USAGE);
+        exit(2);
+    });
+
+    let statistics = match options.attributes {
+        3 => run::<3>(&options),
+        4 => run::<4>(&options),
+        5 => run::<5>(&options),
+        _ => unreachable!("attributes are checked when parsing"),
+    };
+
+    if options.csv {
+        print_csv(&statistics);
+    } else {
+        print_tables(&statistics, &options);
+    }
+}
+
+fn run<const N: usize>(options: &Options) -> Statistics {
+    let variant = if options.ultraset {
+        SetVariant::<N>::UltraSet
+    } else {
+        SetVariant::<N>::Set
+    };
+    simulate(variant, options.policy, options.games, options.seed)
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        ultraset: false,
+        games: 1000,
+        seed: 0,
+        policy: Policy::RandomMove,
+        attributes: 4,
+        csv: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+        match arg.as_str() {
+            "set" => options.ultraset = false,
+            "ultraset" => options.ultraset = true,
+            "--games" => options.games = parse_number(&value("--games")?)?,
+            "--seed" => options.seed = parse_number(&value("--seed")?)?,
+            "--attributes" => {
+                options.attributes = parse_number(&value("--attributes")?)?;
+                if !(3..=5).contains(&options.attributes) {
+                    return Err("decks have 3, 4 or 5 attributes".to_string());
+                }
+            }
+            "--policy" => {
+                options.policy = match value("--policy")?.as_str() {
+                    "first" => Policy::FirstMove,
+                    "random" => Policy::RandomMove,
+                    other => return Err(format!("unknown policy {}", other)),
+                }
+            }
+            "--csv" => options.csv = true,
+            other => return Err(format!("unknown argument {}", other)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} is not a valid number", value))
+}
+
+fn print_tables(statistics: &Statistics, options: &Options) {
+    let game = if options.ultraset { "UltraSet" } else { "Set" };
+    let policy = match options.policy {
+        Policy::FirstMove => "first move",
+        Policy::RandomMove => "random move",
+    };
+    println!(
+        "{} with {} attributes, {} games, {} policy, seed {}",
+        game, options.attributes, statistics.games, policy, options.seed
+    );
+
+    println!();
+    println!(
+        "{:>10}  {:>10}  {:>12}  {:>11}",
+        "Board size", "Boards", "Without move", "Probability"
+    );
+    for (size, count) in &statistics.boards {
+        println!(
+            "{:>10}  {:>10}  {:>12}  {:>11.4}",
+            size,
+            count.seen,
+            count.without_move,
+            count.probability_without_move()
+        );
+    }
+
+    for (title, histogram) in [
+        ("Largest board", &statistics.largest_board),
+        ("Cards left", &statistics.cards_left),
+    ] {
+        println!();
+        println!("{:>13}  {:>10}  {:>8}", title, "Games", "Fraction");
+        for (size, games) in histogram {
+            println!(
+                "{:>13}  {:>10}  {:>8.4}",
+                size,
+                games,
+                *games as f64 / statistics.games as f64
+            );
+        }
+    }
+}
+
+fn print_csv(statistics: &Statistics) {
+    println!("statistic,size,count,without_move");
+    for (size, count) in &statistics.boards {
+        println!("boards,{},{},{}", size, count.seen, count.without_move);
+    }
+    for (size, games) in &statistics.largest_board {
+        println!("largest_board,{},{},", size, games);
+    }
+    for (size, games) in &statistics.cards_left {
+        println!("cards_left,{},{},", size, games);
+    }
+}
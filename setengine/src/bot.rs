@@ -0,0 +1,280 @@
+use crate::{GameDeck, GameVariant, SetVariant};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::time::Duration;
+
+/// How quickly and how reliably a computer player finds sets and ultrasets.
+///
+/// The time to spot a move grows with the number of cards in play and with the number of
+/// attributes on which the cards of the move are not all the same. Every move on the board is
+/// timed independently, and the bot claims whichever it spots first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkillModel {
+    /// Time to spot a move whose cards differ on no attribute, before counting the board
+    pub base_time: Duration,
+    /// Extra time for every card in play
+    pub time_per_card: Duration,
+    /// Extra time for every attribute on which the cards of a move are not all the same
+    pub time_per_attribute: Duration,
+    /// How much times vary: a time `t` is drawn uniformly from `t * (1 - v)` to `t * (1 + v)`.
+    /// Values outside of 0 to 1 are clamped to it, and values that are not finite count as 0.
+    pub time_variation: f64,
+    /// Probability that a claim turns out to be wrong. Values outside of 0 to 1 are clamped
+    /// to it, and values that are not finite count as 0.
+    pub mistake_rate: f64,
+}
+
+/// A claim a computer player would make, and when it would make it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BotMove {
+    /// Indices of the claimed cards in `in_play`
+    pub selection: Vec<usize>,
+    /// Time from when the board was dealt until the claim
+    pub time: Duration,
+    /// Whether the claim is a mistake, in which case `selection` is not a valid play
+    pub mistake: bool,
+}
+
+/// A computer player for Set and UltraSet
+#[derive(Debug, Clone)]
+pub struct Bot {
+    skill: SkillModel,
+    rng: ChaCha8Rng,
+}
+
+impl SkillModel {
+    /// Slow, and often wrong
+    #[must_use]
+    pub fn beginner() -> Self {
+        Self {
+            base_time: Duration::from_secs(6),
+            time_per_card: Duration::from_millis(600),
+            time_per_attribute: Duration::from_secs(3),
+            time_variation: 0.5,
+            mistake_rate: 0.15,
+        }
+    }
+
+    /// About as fast as a regular player
+    #[must_use]
+    pub fn intermediate() -> Self {
+        Self {
+            base_time: Duration::from_secs(3),
+            time_per_card: Duration::from_millis(300),
+            time_per_attribute: Duration::from_millis(1500),
+            time_variation: 0.4,
+            mistake_rate: 0.05,
+        }
+    }
+
+    /// Fast, and rarely wrong
+    #[must_use]
+    pub fn expert() -> Self {
+        Self {
+            base_time: Duration::from_millis(1500),
+            time_per_card: Duration::from_millis(150),
+            time_per_attribute: Duration::from_millis(700),
+            time_variation: 0.3,
+            mistake_rate: 0.01,
+        }
+    }
+
+    /// Expected time to spot a move on a board of `board_size` cards, whose cards differ on
+    /// `differing_attributes` attributes
+    pub fn expected_time(&self, board_size: usize, differing_attributes: usize) -> Duration {
+        self.base_time
+            + self.time_per_card * board_size as u32
+            + self.time_per_attribute * differing_attributes as u32
+    }
+}
+
+impl Bot {
+    /// Creates a bot whose timing and mistakes are random
+    pub fn new(skill: SkillModel) -> Self {
+        Self::from_seed(skill, thread_rng().gen())
+    }
+
+    /// Creates a bot that always makes the same decisions on the same games
+    pub fn from_seed(skill: SkillModel, seed: u64) -> Self {
+        Self {
+            skill,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Returns the skill model of the bot
+    pub fn skill(&self) -> &SkillModel {
+        &self.skill
+    }
+
+    /// Decides which move the bot would claim on the current board, and how long it would take
+    /// to do so. Returns `None` if there is no move in play.
+    pub fn decide<const N: usize>(&mut self, game: &GameDeck<SetVariant<N>>) -> Option<BotMove> {
+        let board_size = game.in_play().len();
+        let skill = self.skill;
+        let time_variation = if skill.time_variation.is_finite() {
+            skill.time_variation.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let (selection, time) = game
+            .all_moves()
+            .into_iter()
+            .map(|selection| {
+                let differing = differing_attributes(game, &selection);
+                let expected = skill.expected_time(board_size, differing);
+                let variation = self.rng.gen_range(-1.0..=1.0) * time_variation;
+                (selection, expected.mul_f64((1.0 + variation).max(0.0)))
+            })
+            .min_by_key(|(_, time)| *time)?;
+
+        let mut bot_move = BotMove {
+            selection,
+            time,
+            mistake: false,
+        };
+        let mistake_rate = if skill.mistake_rate.is_finite() {
+            skill.mistake_rate.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        if self.rng.gen_bool(mistake_rate) {
+            self.spoil(game, &mut bot_move);
+        }
+        Some(bot_move)
+    }
+
+    /// Swaps one card of the move for another card in play, so that it is no longer valid.
+    /// Leaves the move alone if every replacement would still be valid.
+    fn spoil<const N: usize>(&mut self, game: &GameDeck<SetVariant<N>>, bot_move: &mut BotMove) {
+        let position = self.rng.gen_range(0..bot_move.selection.len());
+        let mut replacements: Vec<usize> = (0..game.in_play().len())
+            .filter(|index| !bot_move.selection.contains(index))
+            .collect();
+        replacements.shuffle(&mut self.rng);
+
+        for replacement in replacements {
+            let mut selection = bot_move.selection.clone();
+            selection[position] = replacement;
+            let cards: Vec<_> = selection
+                .iter()
+                .map(|&index| game.in_play()[index])
+                .collect();
+            if !game.variant().is_valid(&cards) {
+                bot_move.selection = selection;
+                bot_move.mistake = true;
+                return;
+            }
+        }
+    }
+}
+
+/// Number of attributes on which the selected cards are not all the same
+fn differing_attributes<const N: usize>(
+    game: &GameDeck<SetVariant<N>>,
+    selection: &[usize],
+) -> usize {
+    let coords: Vec<[u8; N]> = selection
+        .iter()
+        .map(|&index| game.in_play()[index].0.coords())
+        .collect();
+    (0..N)
+        .filter(|&attribute| {
+            coords
+                .iter()
+                .any(|card| card[attribute] != coords[0][attribute])
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bot, SkillModel};
+    use crate::{Deck, GameDeck, PlayResponse};
+    use std::time::Duration;
+
+    #[test]
+    fn bot_plays_valid_moves_unless_mistaken() {
+        let mut game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(4)).unwrap();
+        let mut bot = Bot::from_seed(
+            SkillModel {
+                mistake_rate: 0.3,
+                ..SkillModel::intermediate()
+            },
+            4,
+        );
+
+        let mut mistakes = 0;
+        while let Some(bot_move) = bot.decide(&game) {
+            let max_time = SkillModel::intermediate()
+                .expected_time(game.in_play().len(), 4)
+                .mul_f64(1.4);
+            assert!(bot_move.time <= max_time);
+
+            if bot_move.mistake {
+                mistakes += 1;
+                assert_eq!(
+                    game.play_selection(bot_move.selection),
+                    Ok(PlayResponse::InvalidPlay)
+                );
+            } else if game.play_selection(bot_move.selection) == Ok(PlayResponse::GameOver) {
+                break;
+            }
+        }
+        assert!(mistakes > 0);
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn bot_never_errs_with_non_finite_mistake_rates() {
+        let game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(2)).unwrap();
+        for mistake_rate in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let skill = SkillModel {
+                mistake_rate,
+                ..SkillModel::beginner()
+            };
+            let mut bot = Bot::from_seed(skill, 2);
+            assert!((0..20).all(|_| !bot.decide(&game).unwrap().mistake));
+        }
+    }
+
+    #[test]
+    fn bot_times_stay_in_range_for_any_time_variation() {
+        let game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(3)).unwrap();
+        let slowest = SkillModel::intermediate()
+            .expected_time(game.in_play().len(), 4)
+            .mul_f64(2.0);
+        for time_variation in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300, -1e300] {
+            let skill = SkillModel {
+                time_variation,
+                ..SkillModel::intermediate()
+            };
+            let mut bot = Bot::from_seed(skill, 3);
+            assert!((0..20).all(|_| bot.decide(&game).unwrap().time <= slowest));
+        }
+    }
+
+    #[test]
+    fn differing_attributes_slow_the_bot_down() {
+        let skill = SkillModel {
+            time_variation: 0.0,
+            ..SkillModel::expert()
+        };
+        let game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(9)).unwrap();
+        let bot_move = Bot::from_seed(skill, 0).decide(&game).unwrap();
+
+        let fastest = game
+            .all_moves()
+            .iter()
+            .map(|selection| {
+                let differing = super::differing_attributes(&game, selection);
+                skill.expected_time(game.in_play().len(), differing)
+            })
+            .min()
+            .unwrap();
+        assert_eq!(bot_move.time, fastest);
+        assert!(bot_move.time >= skill.expected_time(game.in_play().len(), 1));
+        assert!(bot_move.time > Duration::ZERO);
+    }
+}
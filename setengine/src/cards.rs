@@ -1,79 +1,169 @@
+use crate::tables::{ADDITION, COMPLETION, MAX_ATTRIBUTES, MAX_CARDS, NEGATION};
 use cardgen::CardVisualAttr;
 use itertools::Itertools;
+use std::fmt;
 use std::ops::{Add, Sub};
 
-/// Coordinates in F_3^4 of a card
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct CardCoordinates {
-    num: u8,
-    color: u8,
-    shape: u8,
-    filling: u8,
+/// Coordinates in F_3^N of a card. The standard deck has `N = 4` attributes, Set Junior has
+/// `N = 3`, and decks with up to five attributes are supported.
+///
+/// The coordinates are packed into a single base 3 index, with the first attribute as the
+/// most significant digit, and arithmetic is done through precomputed tables.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CardCoordinates<const N: usize = 4> {
+    index: u8,
 }
 
-/// Checks if selection is a set. Panics if slice has less than 3 elements.
-pub fn selection_is_set(cards_picked: &[(CardCoordinates, CardVisualAttr)]) -> bool {
-    let card1 = (cards_picked[0]).0;
-    let card2 = (cards_picked[1]).0;
-    let card3 = (cards_picked[2]).0;
+/// The ultrasets on a board that share the same ghost card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UltrasetGroup<const N: usize = 4> {
+    /// The card that completes both pairs of every ultraset in the group to a set.
+    pub ghost: CardCoordinates<N>,
+    /// Indices of the ultrasets. The first two and the last two cards of each quadruple
+    /// complete to `ghost`.
+    pub ultrasets: Vec<[usize; 4]>,
+}
 
-    is_set(card1, card2, card3)
+/// Checks if selection is a set. A selection that does not have exactly 3 cards is not a set.
+pub fn selection_is_set<const N: usize>(
+    cards_picked: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> bool {
+    match cards_picked {
+        [card1, card2, card3] => is_set(card1.0, card2.0, card3.0),
+        _ => false,
+    }
 }
 
 /// Checks if selection contains a set.
-pub fn selection_contains_set(cards_in_play: &[(CardCoordinates, CardVisualAttr)]) -> bool {
-    for triple in cards_in_play.iter().combinations(3) {
-        let card1 = (*triple[0]).0;
-        let card2 = (*triple[1]).0;
-        let card3 = (*triple[2]).0;
-        if is_set(card1, card2, card3) {
-            return true;
-        }
+pub fn selection_contains_set<const N: usize>(
+    cards_in_play: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> bool {
+    find_set(cards_in_play).is_some()
+}
+
+/// Checks if selection is an ultraset. A selection that does not have exactly 4 cards is not
+/// an ultraset.
+pub fn selection_is_ultraset<const N: usize>(
+    cards_picked: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> bool {
+    match cards_picked {
+        [card1, card2, card3, card4] => is_ultraset(card1.0, card2.0, card3.0, card4.0),
+        _ => false,
+    }
+}
+
+/// Checks if selection contains an ultraset.
+pub fn selection_contains_ultraset<const N: usize>(
+    cards_in_play: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> bool {
+    find_ultraset(cards_in_play).is_some()
+}
+
+/// Returns the indices of every set in `cards`, each in increasing order.
+pub fn find_all_sets<const N: usize>(
+    cards: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> Vec<[usize; 3]> {
+    let mut positions = [None; MAX_CARDS];
+    for (index, card) in cards.iter().enumerate() {
+        positions[card.0.index()] = Some(index);
     }
-    false
+
+    (0..cards.len())
+        .tuple_combinations()
+        .filter_map(|(i, j)| {
+            let completion = complete_set(cards[i].0, cards[j].0);
+            match positions[completion.index()] {
+                Some(k) if k > j => Some([i, j, k]),
+                _ => None,
+            }
+        })
+        .collect()
 }
 
-/// Checks if selection is an ultraset. Panics if slice has less than 4 elements.
-pub fn selection_is_ultraset(cards_picked: &[(CardCoordinates, CardVisualAttr)]) -> bool {
-    let card1 = (cards_picked[0]).0;
-    let card2 = (cards_picked[1]).0;
-    let card3 = (cards_picked[2]).0;
-    let card4 = (cards_picked[3]).0;
+/// Returns every ultraset in `cards`, grouped by ghost card in increasing order of ghost. An
+/// ultraset has only one pairing whose pairs complete to the same card, so every ultraset
+/// appears exactly once.
+pub fn find_all_ultrasets<const N: usize>(
+    cards: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> Vec<UltrasetGroup<N>> {
+    let mut completions: Vec<Vec<(usize, usize)>> = vec![Vec::new(); MAX_CARDS];
+    for (i, j) in (0..cards.len()).tuple_combinations() {
+        let completion = complete_set(cards[i].0, cards[j].0);
+        completions[completion.index()].push((i, j));
+    }
 
-    is_ultraset(card1, card2, card3, card4)
+    completions
+        .into_iter()
+        .enumerate()
+        .filter(|(_, pairs)| pairs.len() > 1)
+        .map(|(ghost, pairs)| UltrasetGroup {
+            ghost: CardCoordinates { index: ghost as u8 },
+            ultrasets: pairs
+                .iter()
+                .tuple_combinations()
+                .map(|(&(i, j), &(k, l))| [i, j, k, l])
+                .collect(),
+        })
+        .collect()
 }
 
-/// Checks if selection contains an ultraset.
-pub fn selection_contains_ultraset(cards_in_play: &[(CardCoordinates, CardVisualAttr)]) -> bool {
-    for quadruple in cards_in_play.iter().combinations(4) {
-        let card1 = (*quadruple[0]).0;
-        let card2 = (*quadruple[1]).0;
-        let card3 = (*quadruple[2]).0;
-        let card4 = (*quadruple[3]).0;
-        if is_ultraset(card1, card2, card3, card4) {
-            return true;
+/// Returns the indices of a set in `cards`, if there is one. Every pair of cards lies in
+/// exactly one set, so it is enough to look up the completion of each pair.
+pub(crate) fn find_set<const N: usize>(
+    cards: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> Option<[usize; 3]> {
+    let mut positions = [None; MAX_CARDS];
+    for (index, card) in cards.iter().enumerate() {
+        positions[card.0.index()] = Some(index);
+    }
+
+    (0..cards.len()).tuple_combinations().find_map(|(i, j)| {
+        let completion = complete_set(cards[i].0, cards[j].0);
+        positions[completion.index()].map(|k| [i, j, k])
+    })
+}
+
+/// Returns the indices of an ultraset in `cards`, if there is one. Pairs are bucketed by
+/// their completion; two distinct pairs with the same completion cannot share a card, so the
+/// first bucket to receive a second pair gives an ultraset.
+pub(crate) fn find_ultraset<const N: usize>(
+    cards: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> Option<[usize; 4]> {
+    let mut completions: [Option<(usize, usize)>; MAX_CARDS] = [None; MAX_CARDS];
+
+    for (i, j) in (0..cards.len()).tuple_combinations() {
+        let completion = complete_set(cards[i].0, cards[j].0);
+        match completions[completion.index()] {
+            Some((k, l)) => return Some([k, l, i, j]),
+            None => completions[completion.index()] = Some((i, j)),
         }
     }
-    false
+    None
 }
 
-pub(crate) fn is_set(
-    card1: CardCoordinates,
-    card2: CardCoordinates,
-    card3: CardCoordinates,
+pub(crate) fn is_set<const N: usize>(
+    card1: CardCoordinates<N>,
+    card2: CardCoordinates<N>,
+    card3: CardCoordinates<N>,
 ) -> bool {
-    (card1 + card2 + card3) == CardCoordinates::new(0, 0, 0, 0)
+    complete_set(card1, card2) == card3
 }
 
-fn complete_set(card1: CardCoordinates, card2: CardCoordinates) -> CardCoordinates {
-    card1 + card1 - card2
+pub(crate) fn complete_set<const N: usize>(
+    card1: CardCoordinates<N>,
+    card2: CardCoordinates<N>,
+) -> CardCoordinates<N> {
+    CardCoordinates {
+        index: COMPLETION[card1.index()][card2.index()],
+    }
 }
 
-pub(crate) fn is_ultraset(
-    card1: CardCoordinates,
-    card2: CardCoordinates,
-    card3: CardCoordinates,
-    card4: CardCoordinates,
+pub(crate) fn is_ultraset<const N: usize>(
+    card1: CardCoordinates<N>,
+    card2: CardCoordinates<N>,
+    card3: CardCoordinates<N>,
+    card4: CardCoordinates<N>,
 ) -> bool {
     complete_set(card1, card2) == complete_set(card3, card4)
         || complete_set(card1, card3) == complete_set(card2, card4)
@@ -83,71 +173,121 @@ pub(crate) fn is_ultraset(
 impl CardCoordinates {
     /// Creates a coordinate from a quadruple of u8s.
     pub fn new(num: u8, color: u8, shape: u8, filling: u8) -> Self {
-        Self {
-            num: num % 3,
-            color: color % 3,
-            shape: shape % 3,
-            filling: filling % 3,
+        Self::from_array([num, color, shape, filling])
+    }
+}
+
+impl<const N: usize> CardCoordinates<N> {
+    /// Number of distinct cards with `N` attributes, i.e. the size of F_3^N.
+    pub const DECK_SIZE: usize = {
+        assert!(N <= MAX_ATTRIBUTES, "cards have at most five attributes");
+        3usize.pow(N as u32)
+    };
+
+    /// Creates a coordinate from an array of u8s, reducing each entry mod 3.
+    pub fn from_array(coords: [u8; N]) -> Self {
+        let index = coords
+            .iter()
+            .fold(0, |index, coord| 3 * index + (coord % 3) as usize);
+        Self::from_index(index)
+    }
+
+    /// Creates a coordinate from its index in `0..DECK_SIZE`, the base 3 number whose digits
+    /// are the attributes. Panics if the index is out of range.
+    pub fn from_index(index: usize) -> Self {
+        assert!(index < Self::DECK_SIZE, "card index out of range");
+        Self { index: index as u8 }
+    }
+
+    /// The index of this card in `0..DECK_SIZE`.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// The origin of F_3^N.
+    pub fn zero() -> Self {
+        Self { index: 0 }
+    }
+
+    /// Returns the value of every attribute, each in `0..3`.
+    pub fn coords(&self) -> [u8; N] {
+        let mut index = self.index;
+        let mut coords = [0; N];
+        for coord in coords.iter_mut().rev() {
+            *coord = index % 3;
+            index /= 3;
+        }
+        coords
+    }
+
+    /// Iterates over every point of F_3^N, with the last attribute varying fastest.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..Self::DECK_SIZE).map(Self::from_index)
+    }
+}
+
+impl<const N: usize> fmt::Debug for CardCoordinates<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CardCoordinates")
+            .field("coords", &self.coords())
+            .finish()
+    }
+}
+
+/// Serialized as the array of attribute values, e.g. `[0, 1, 2, 1]`.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for CardCoordinates<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.coords().as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for CardCoordinates<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let coords: Vec<u8> = serde::Deserialize::deserialize(deserializer)?;
+        let coords: [u8; N] = coords.try_into().map_err(|coords: Vec<u8>| {
+            D::Error::invalid_length(coords.len(), &format!("{} attributes", N).as_str())
+        })?;
+        if coords.iter().any(|&coord| coord >= 3) {
+            return Err(D::Error::custom("attribute values must be less than 3"));
         }
+        Ok(Self::from_array(coords))
     }
 }
 
-impl Add for CardCoordinates {
+impl<const N: usize> Add for CardCoordinates<N> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        let Self {
-            num: lhs_num,
-            color: lhs_color,
-            shape: lhs_shape,
-            filling: lhs_filling,
-        } = self;
-        let Self {
-            num: rhs_num,
-            color: rhs_color,
-            shape: rhs_shape,
-            filling: rhs_filling,
-        } = rhs;
-
-        Self::new(
-            lhs_num + rhs_num,
-            lhs_color + rhs_color,
-            lhs_shape + rhs_shape,
-            lhs_filling + rhs_filling,
-        )
-    }
-}
-
-impl Sub for CardCoordinates {
+        Self {
+            index: ADDITION[self.index()][rhs.index()],
+        }
+    }
+}
+
+impl<const N: usize> Sub for CardCoordinates<N> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        let Self {
-            num: lhs_num,
-            color: lhs_color,
-            shape: lhs_shape,
-            filling: lhs_filling,
-        } = self;
-        let Self {
-            num: rhs_num,
-            color: rhs_color,
-            shape: rhs_shape,
-            filling: rhs_filling,
-        } = rhs;
-
-        Self::new(
-            lhs_num + rhs_num + rhs_num,
-            lhs_color + rhs_color + rhs_color,
-            lhs_shape + rhs_shape + rhs_shape,
-            lhs_filling + rhs_filling + rhs_filling,
-        )
+        Self {
+            index: ADDITION[self.index()][NEGATION[rhs.index()] as usize],
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{complete_set, is_set, CardCoordinates};
+    use super::{
+        complete_set, find_all_sets, find_all_ultrasets, find_set, find_ultraset, is_set,
+        is_ultraset, CardCoordinates,
+    };
+    use crate::Deck;
+    use itertools::Itertools;
     use quickcheck::{Arbitrary, Gen};
+    use std::collections::HashSet;
 
     impl Arbitrary for CardCoordinates {
         fn arbitrary(g: &mut Gen) -> Self {
@@ -165,4 +305,121 @@ mod tests {
         let card3 = complete_set(card1, card2);
         is_set(card1, card2, card3)
     }
+
+    #[quickcheck]
+    fn table_arithmetic_matches_coordinatewise(
+        card1: CardCoordinates,
+        card2: CardCoordinates,
+    ) -> bool {
+        let (coords1, coords2) = (card1.coords(), card2.coords());
+        let sum: [u8; 4] = std::array::from_fn(|i| coords1[i] + coords2[i]);
+        let difference: [u8; 4] = std::array::from_fn(|i| coords1[i] + 3 - coords2[i]);
+
+        card1 + card2 == CardCoordinates::from_array(sum)
+            && card1 - card2 == CardCoordinates::from_array(difference)
+            && complete_set(card1, card2) == CardCoordinates::zero() - card1 - card2
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cards_serialize_to_the_documented_schema() {
+        use cardgen::{CardVisualAttr, Filling, SetColor, SetNum, Shape};
+
+        let card = (
+            CardCoordinates::new(0, 1, 2, 1),
+            CardVisualAttr {
+                num: SetNum::One,
+                color: SetColor::Red,
+                shape: Shape::Squiggle,
+                filling: Filling::Solid,
+            },
+        );
+        let json =
+            r#"[[0,1,2,1],{"num":"One","color":"Red","shape":"Squiggle","filling":"Solid"}]"#;
+
+        assert_eq!(serde_json::to_string(&card).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<(CardCoordinates, CardVisualAttr)>(json).unwrap(),
+            card
+        );
+        assert!(serde_json::from_str::<CardCoordinates>("[0,1,3,1]").is_err());
+        assert!(serde_json::from_str::<CardCoordinates>("[0,1,2]").is_err());
+    }
+
+    #[test]
+    fn all_enumerates_every_card_once() {
+        let junior: HashSet<CardCoordinates<3>> = CardCoordinates::all().collect();
+        let standard: HashSet<CardCoordinates<4>> = CardCoordinates::all().collect();
+        let expert: HashSet<CardCoordinates<5>> = CardCoordinates::all().collect();
+
+        assert_eq!(junior.len(), 27);
+        assert_eq!(standard.len(), 81);
+        assert_eq!(expert.len(), 243);
+    }
+
+    #[quickcheck]
+    fn find_set_agrees_with_brute_force(seed: u64, size: u8) -> bool {
+        let deck = Deck::<4>::standard_from_seed(seed);
+        let cards = &deck.cards[..(size % 22) as usize];
+
+        let brute_force = cards
+            .iter()
+            .tuple_combinations()
+            .any(|(a, b, c)| is_set(a.0, b.0, c.0));
+
+        match find_set(cards) {
+            Some([i, j, k]) => brute_force && is_set(cards[i].0, cards[j].0, cards[k].0),
+            None => !brute_force,
+        }
+    }
+
+    #[quickcheck]
+    fn find_ultraset_agrees_with_brute_force(seed: u64, size: u8) -> bool {
+        let deck = Deck::<4>::standard_from_seed(seed);
+        let cards = &deck.cards[..(size % 13) as usize];
+
+        let brute_force = cards
+            .iter()
+            .tuple_combinations()
+            .any(|(a, b, c, d)| is_ultraset(a.0, b.0, c.0, d.0));
+
+        match find_ultraset(cards) {
+            Some([i, j, k, l]) => {
+                brute_force
+                    && [i, j, k, l].iter().all_unique()
+                    && is_ultraset(cards[i].0, cards[j].0, cards[k].0, cards[l].0)
+            }
+            None => !brute_force,
+        }
+    }
+
+    #[quickcheck]
+    fn find_all_counts_agree_with_brute_force(seed: u64, size: u8) -> bool {
+        let deck = Deck::<4>::standard_from_seed(seed);
+        let cards = &deck.cards[..(size % 16) as usize];
+
+        let set_count = cards
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b, c)| is_set(a.0, b.0, c.0))
+            .count();
+        let ultraset_count = cards
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b, c, d)| is_ultraset(a.0, b.0, c.0, d.0))
+            .count();
+
+        let groups = find_all_ultrasets(cards);
+        let ghosts_correct = groups.iter().all(|group| {
+            group.ultrasets.iter().all(|&[i, j, k, l]| {
+                complete_set(cards[i].0, cards[j].0) == group.ghost
+                    && complete_set(cards[k].0, cards[l].0) == group.ghost
+            })
+        });
+        let found_ultrasets: usize = groups.iter().map(|group| group.ultrasets.len()).sum();
+
+        find_all_sets(cards).len() == set_count
+            && found_ultrasets == ultraset_count
+            && ghosts_correct
+    }
 }
@@ -1,327 +1,1000 @@
 use crate::{
-    cards::{selection_contains_set, selection_contains_ultraset},
-    selection_is_set, selection_is_ultraset,
+    find_all_sets, find_all_ultrasets, CardCoordinates, GameRules, GameVariant, SetVariant,
+    UltrasetGroup,
+};
+use cardgen::{
+    generate_partially_random_attributes_with_rng, generate_random_attributes_with_rng,
+    generate_standard_attributes, AttributeRandomization, Attributes, CardVisualAttr,
 };
-use cardgen::{generate_random_attributes, generate_standard_attributes, CardVisualAttr};
-use itertools::Itertools;
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
 
-use crate::CardCoordinates;
-
-/// A shuffled deck
+/// A shuffled deck of cards with `N` attributes
 #[derive(Clone)]
-pub struct Deck {
-    pub cards: Vec<(CardCoordinates, CardVisualAttr)>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Deck<const N: usize = 4> {
+    pub cards: Vec<(CardCoordinates<N>, CardVisualAttr)>,
 }
 
 /// A deck from which cards have been dealt out of
 #[derive(Clone)]
-pub struct ActiveDeck {
-    in_play: Vec<(CardCoordinates, CardVisualAttr)>,
-    in_deck: Vec<(CardCoordinates, CardVisualAttr)>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActiveDeck<C> {
+    in_play: Vec<C>,
+    in_deck: Vec<C>,
+    rules: GameRules,
 }
 
-/// Enum wrapping `ActiveDeck` marking whether Set or UltraSet is being played
+/// A game in progress: the variant being played, the cards in play and in the deck, and the
+/// moves made so far
 #[derive(Clone)]
-pub enum GameDeck {
-    Set(ActiveDeck),
-    UltraSet(ActiveDeck),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "V: serde::Serialize, V::Card: serde::Serialize",
+        deserialize = "V: serde::Deserialize<'de>, V::Card: serde::Deserialize<'de>"
+    ))
+)]
+pub struct GameDeck<V: GameVariant = SetVariant> {
+    variant: V,
+    active_deck: ActiveDeck<V::Card>,
+    history: Vec<Move<V::Card>>,
+    undone: Vec<Move<V::Card>>,
+}
+
+/// A record of one accepted play, or of a request for more cards, with everything needed to
+/// undo it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move<C> {
+    /// Indices of the played cards in `in_play` before the move, in increasing order. Empty
+    /// if the move was a call to `GameDeck::deal_more`.
+    pub selection: Vec<usize>,
+    /// The played cards, in the same order as `selection`.
+    pub removed: Vec<C>,
+    /// The cards dealt from the deck during the move in the order they were dealt, each with
+    /// its index in `in_play` after the move.
+    pub dealt: Vec<(usize, C)>,
 }
 
 /// The three possible responses to playing a triple/quadruple.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlayResponse {
     InvalidPlay,
     ValidPlay,
     GameOver,
 }
 
-impl GameDeck {
+/// Reasons a deal, hint or play is rejected. The game is left unchanged when this is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlayError {
+    /// The selection does not have as many cards as the game needs. When plays vary in size,
+    /// `expected` is the allowed size closest to the one found.
+    WrongSelectionSize { expected: usize, found: usize },
+    /// An index in the selection does not point to a card in play.
+    IndexOutOfRange { index: usize, in_play: usize },
+    /// The same index appears more than once in the selection.
+    DuplicateIndex(usize),
+    /// There is no set/ultraset in play, and no more cards can be dealt to make one.
+    NoMoveAvailable,
+    /// No more cards can be dealt, because the deck is empty or the board is full.
+    CannotDeal,
+    /// No move has been made since the start of the game, or every move has been undone.
+    NothingToUndo,
+    /// No move has been undone since the last play.
+    NothingToRedo,
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayError::WrongSelectionSize { expected, found } => {
+                write!(
+                    f,
+                    "expected {} cards to be selected, found {}",
+                    expected, found
+                )
+            }
+            PlayError::IndexOutOfRange { index, in_play } => write!(
+                f,
+                "index {} is out of range for {} cards in play",
+                index, in_play
+            ),
+            PlayError::DuplicateIndex(index) => {
+                write!(f, "index {} is selected more than once", index)
+            }
+            PlayError::NoMoveAvailable => write!(f, "no move is available"),
+            PlayError::CannotDeal => write!(f, "no more cards can be dealt"),
+            PlayError::NothingToUndo => write!(f, "there is no move to undo"),
+            PlayError::NothingToRedo => write!(f, "there is no move to redo"),
+        }
+    }
+}
+
+impl std::error::Error for PlayError {}
+
+impl<V: GameVariant> GameDeck<V> {
+    /// Deals out `cards` for a game of `variant` with the variant's default rules. Fails if
+    /// the rules guarantee a move and the cards never contain one.
+    pub fn start(variant: V, cards: &[V::Card]) -> Result<Self, PlayError> {
+        let rules = variant.default_rules();
+        Self::start_with_rules(variant, cards, rules)
+    }
+
+    /// Deals out `cards` for a game of `variant` with the given rules.
+    pub fn start_with_rules(
+        variant: V,
+        cards: &[V::Card],
+        rules: GameRules,
+    ) -> Result<Self, PlayError> {
+        let active_deck = ActiveDeck::deal(cards, rules, |cards| variant.contains_move(cards))?;
+        Ok(Self {
+            variant,
+            active_deck,
+            history: Vec::new(),
+            undone: Vec::new(),
+        })
+    }
+
+    /// Returns the variant being played
+    pub fn variant(&self) -> &V {
+        &self.variant
+    }
+
     /// Returns how many cards need to be selected, depending upon the game
     pub fn selection_size(&self) -> usize {
-        match *self {
-            GameDeck::Set(_) => 3,
-            GameDeck::UltraSet(_) => 4,
-        }
+        self.variant.selection_size()
     }
 
-    /// Deals out cards, ensuring there is always a set.
-    pub fn start_set_play(deck: &Deck) -> Self {
-        let mut initial_cards = 12;
-        while !selection_contains_set(&deck.cards[0..initial_cards]) {
-            initial_cards += 3;
-        }
-        let in_play = deck.cards[..initial_cards].to_vec();
-        let in_deck = deck.cards[initial_cards..].to_vec();
+    /// Returns the dealing rules of this game
+    pub fn rules(&self) -> &GameRules {
+        &self.active_deck.rules
+    }
 
-        GameDeck::Set(ActiveDeck { in_play, in_deck })
+    /// Returns indices of cards that form a valid play after completing with one more card.
+    /// Fails if there is no valid play in play, which only happens once the game is over.
+    pub fn get_hint(&self) -> Result<Vec<usize>, PlayError> {
+        self.variant
+            .hint(self.in_play())
+            .ok_or(PlayError::NoMoveAvailable)
     }
 
-    /// Deals out cards, ensuring there is always an ultraset.
-    pub fn start_ultraset_play(deck: &Deck) -> Self {
-        let mut initial_cards = 12;
-        while !selection_contains_ultraset(&deck.cards[0..initial_cards]) {
-            initial_cards += 3;
-        }
-        let in_play = deck.cards[..initial_cards].to_vec();
-        let in_deck = deck.cards[initial_cards..].to_vec();
+    /// Checks if the game is over: nothing to play, and no cards can be dealt.
+    pub fn is_over(&self) -> bool {
+        !self.variant.contains_move(self.in_play()) && !self.can_deal()
+    }
 
-        GameDeck::UltraSet(ActiveDeck { in_play, in_deck })
+    fn can_deal(&self) -> bool {
+        let ad = &self.active_deck;
+        ad.rules.deal_size(ad.in_play.len(), ad.in_deck.len()) > 0
     }
 
-    /// Returns indices of 2/3 cards that form set/ultraset after completing with one more card
-    pub fn get_hint(&self) -> Vec<usize> {
-        match *self {
-            Self::Set(_) => self.get_set_hint(),
-            Self::UltraSet(_) => self.get_ultraset_hint(),
-        }
+    /// Deals `refill_size` more cards, or as many as the deck and board size allow. This is
+    /// how players ask for more cards when the rules do not guarantee a move.
+    pub fn deal_more(&mut self) -> Result<(), PlayError> {
+        let record = self.apply_deal().ok_or(PlayError::CannotDeal)?;
+        self.history.push(record);
+        self.undone.clear();
+        Ok(())
     }
 
-    /// Returns indices of two cards that complete to a set in the cards currently in play
-    fn get_set_hint(&self) -> Vec<usize> {
-        for triple in self.in_play().iter().enumerate().combinations(3) {
-            let card1 = (triple[0]).1 .0;
-            let card2 = (triple[1]).1 .0;
-            let card3 = (triple[2]).1 .0;
-            if crate::cards::is_set(card1, card2, card3) {
-                return vec![(triple[0]).0, (triple[1]).0];
-            }
+    /// Returns every move made so far, oldest first. The last entry is the move that was just
+    /// made, and the one `undo` takes back.
+    pub fn history(&self) -> &[Move<V::Card>] {
+        &self.history
+    }
+
+    /// Returns how many sets or ultrasets have been played so far, leaving out deals
+    pub fn plays_made(&self) -> usize {
+        self.history
+            .iter()
+            .filter(|record| !record.selection.is_empty())
+            .count()
+    }
+
+    /// Takes back the last move, returning the played cards to their places on the board and
+    /// the dealt cards to the top of the deck.
+    pub fn undo(&mut self) -> Result<(), PlayError> {
+        let record = self.history.pop().ok_or(PlayError::NothingToUndo)?;
+        let ActiveDeck {
+            in_play, in_deck, ..
+        } = &mut self.active_deck;
+
+        for &(_, card) in record.dealt.iter().rev() {
+            in_deck.push(card);
         }
-        unreachable!()
-    }
-
-    /// Returns indices of three cards that complete to an ultraset in the cards currently in play
-    fn get_ultraset_hint(&self) -> Vec<usize> {
-        for quadruple in self.in_play().iter().enumerate().combinations(4) {
-            let card1 = (quadruple[0]).1 .0;
-            let card2 = (quadruple[1]).1 .0;
-            let card3 = (quadruple[2]).1 .0;
-            let card4 = (quadruple[3]).1 .0;
-            if crate::cards::is_ultraset(card1, card2, card3, card4) {
-                return vec![(quadruple[0]).0, (quadruple[1]).0, (quadruple[2]).0];
-            }
+
+        // The cards left on the board after taking off the dealt ones are in the order they
+        // were before the move, so the played cards can be put back in increasing order.
+        let mut dealt_indices: Vec<usize> = record.dealt.iter().map(|&(index, _)| index).collect();
+        dealt_indices.sort_by(|a, b| b.cmp(a));
+        for index in dealt_indices {
+            in_play.remove(index);
+        }
+        for (&index, &card) in record.selection.iter().zip(&record.removed) {
+            in_play.insert(index, card);
         }
-        unreachable!()
+
+        self.undone.push(record);
+        Ok(())
+    }
+
+    /// Makes the last undone move again. The same cards are dealt as the first time around.
+    /// Undone moves can be redone until a new move is made.
+    pub fn redo(&mut self) -> Result<(), PlayError> {
+        let undone = self.undone.pop().ok_or(PlayError::NothingToRedo)?;
+        let record = if undone.selection.is_empty() {
+            self.apply_deal().ok_or(PlayError::CannotDeal)?
+        } else {
+            let mut selection = undone.selection;
+            selection.reverse();
+            self.apply_play(&selection).1
+        };
+        self.history.push(record);
+        Ok(())
     }
 
     /// Returns a slice of active cards
-    pub fn in_play(&self) -> &[(CardCoordinates, CardVisualAttr)] {
-        match self {
-            GameDeck::Set(ad) => &ad.in_play,
-            GameDeck::UltraSet(ad) => &ad.in_play,
-        }
+    pub fn in_play(&self) -> &[V::Card] {
+        &self.active_deck.in_play
     }
 
     /// Returns a mutable reference to the Vec of active cards
-    pub fn in_play_mut(&mut self) -> &mut Vec<(CardCoordinates, CardVisualAttr)> {
-        match self {
-            GameDeck::Set(ad) => &mut ad.in_play,
-            GameDeck::UltraSet(ad) => &mut ad.in_play,
-        }
+    pub fn in_play_mut(&mut self) -> &mut Vec<V::Card> {
+        &mut self.active_deck.in_play
     }
 
     /// Returns a slice of cards still in the deck
-    pub fn in_deck(&self) -> &[(CardCoordinates, CardVisualAttr)] {
-        match self {
-            GameDeck::Set(ad) => &ad.in_deck,
-            GameDeck::UltraSet(ad) => &ad.in_deck,
-        }
+    pub fn in_deck(&self) -> &[V::Card] {
+        &self.active_deck.in_deck
     }
 
     /// Returns a mutable reference to the Vec of cards in deck
-    pub fn in_deck_mut(&mut self) -> &mut Vec<(CardCoordinates, CardVisualAttr)> {
-        match self {
-            GameDeck::Set(ad) => &mut ad.in_deck,
-            GameDeck::UltraSet(ad) => &mut ad.in_deck,
-        }
+    pub fn in_deck_mut(&mut self) -> &mut Vec<V::Card> {
+        &mut self.active_deck.in_deck
     }
 
-    /// Plays the cards with the selected indices from the `in_play` buffer, and deals out new cards. Panics if selection is not the right length.
-    pub fn play_selection(&mut self, mut selection: Vec<usize>) -> PlayResponse {
+    /// Plays the cards with the selected indices from the `in_play` buffer, and deals out new cards.
+    /// Fails without changing the game if the selection has the wrong length, or has indices
+    /// that are out of range or repeated.
+    pub fn play_selection(&mut self, mut selection: Vec<usize>) -> Result<PlayResponse, PlayError> {
         selection.sort_by(|a, b| b.cmp(a));
+        self.check_selection(&selection)?;
 
         let mut selected_cards = Vec::new();
         for index in &selection {
             selected_cards.push(self.in_play()[*index]);
         }
 
-        // Case when selection is set or ultraset
-        match &self {
-            GameDeck::Set(_) => {
-                if selection_is_set(&selected_cards) {
-                    if self.in_deck().len() >= 3 {
-                        // Enough cards in deck to replace
-                        if self.in_play().len() <= 12 {
-                            for index in &selection {
-                                self.in_play_mut()[*index] = self.in_deck_mut().pop().unwrap();
-                            }
-                        } else {
-                            // The case when there are 15 or more cards
-                            for index in &selection {
-                                self.in_play_mut().remove(*index);
-                            }
-                        }
-
-                        // Add more cards until in_play has set
-                        while !selection_contains_set(self.in_play()) {
-                            if self.in_deck().is_empty() {
-                                return PlayResponse::GameOver;
-                            }
-                            for _ in 0..3 {
-                                if let Some(card) = self.in_deck_mut().pop() {
-                                    self.in_play_mut().push(card);
-                                }
-                            }
-                        }
-
-                        return PlayResponse::ValidPlay;
-                    } else {
-                        // Not enough cards to replace
-                        for index in selection {
-                            self.in_play_mut().remove(index);
-                        }
-                        while !selection_contains_set(self.in_play()) {
-                            if self.in_deck().is_empty() {
-                                return PlayResponse::GameOver;
-                            }
-                            for _ in 0..3 {
-                                if let Some(card) = self.in_deck_mut().pop() {
-                                    self.in_play_mut().push(card);
-                                }
-                            }
-                        }
-                        return PlayResponse::ValidPlay;
-                    }
-                }
+        if !self.variant.is_valid(&selected_cards) {
+            return Ok(PlayResponse::InvalidPlay);
+        }
+
+        let (response, record) = self.apply_play(&selection);
+        self.history.push(record);
+        self.undone.clear();
+        Ok(response)
+    }
+
+    /// Checks that `selection`, sorted in decreasing order, has the right length and only
+    /// distinct indices of cards in play.
+    pub(crate) fn check_selection(&self, selection: &[usize]) -> Result<(), PlayError> {
+        let (min_size, max_size) = (self.variant.min_selection_size(), self.selection_size());
+        if selection.len() < min_size || selection.len() > max_size {
+            return Err(PlayError::WrongSelectionSize {
+                expected: selection.len().clamp(min_size, max_size),
+                found: selection.len(),
+            });
+        }
+
+        if let Some(&index) = selection
+            .iter()
+            .find(|&&index| index >= self.in_play().len())
+        {
+            return Err(PlayError::IndexOutOfRange {
+                index,
+                in_play: self.in_play().len(),
+            });
+        }
+        if let Some(pair) = selection.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(PlayError::DuplicateIndex(pair[0]));
+        }
+        Ok(())
+    }
+
+    /// Plays the cards at the indices in `selection`, which must be sorted in decreasing
+    /// order, and deals out new cards.
+    fn apply_play(&mut self, selection: &[usize]) -> (PlayResponse, Move<V::Card>) {
+        let ActiveDeck {
+            in_play,
+            in_deck,
+            rules,
+        } = &mut self.active_deck;
+
+        let removed: Vec<V::Card> = selection
+            .iter()
+            .rev()
+            .map(|&index| in_play[index])
+            .collect();
+        // Tracks which cards on the board replaced played cards, and in what order
+        let mut replaced_by: Vec<Option<usize>> = vec![None; in_play.len()];
+        let mut replacements = 0;
+
+        // Shrink the board back if it has grown, otherwise replace the played cards in place.
+        // The selection is sorted in decreasing order, so removals do not shift the indices
+        // still to be handled.
+        let mut extra_cards = if rules.collapse_extra_cards {
+            in_play.len().saturating_sub(rules.initial_board_size)
+        } else {
+            0
+        };
+        for &index in selection {
+            if extra_cards > 0 {
+                in_play.remove(index);
+                replaced_by.remove(index);
+                extra_cards -= 1;
+            } else if let Some(card) = in_deck.pop() {
+                in_play[index] = card;
+                replaced_by[index] = Some(replacements);
+                replacements += 1;
+            } else {
+                in_play.remove(index);
+                replaced_by.remove(index);
             }
-            GameDeck::UltraSet(_) => {
-                if selection_is_ultraset(&selected_cards) {
-                    if self.in_deck().len() >= 4 {
-                        // Enough cards in deck to replace
-                        if self.in_play().len() <= 12 {
-                            for index in &selection {
-                                self.in_play_mut()[*index] = self.in_deck_mut().pop().unwrap();
-                            }
-                        } else {
-                            // The case when there are more than 12 cards
-                            for index in &selection {
-                                self.in_play_mut().remove(*index);
-                            }
-                        }
-
-                        // Add more cards until in_play has set
-                        while !selection_contains_ultraset(self.in_play()) {
-                            if self.in_deck().is_empty() {
-                                return PlayResponse::GameOver;
-                            }
-                            for _ in 0..4 {
-                                if let Some(card) = self.in_deck_mut().pop() {
-                                    self.in_play_mut().push(card);
-                                }
-                            }
-                        }
-
-                        return PlayResponse::ValidPlay;
-                    } else {
-                        // Not enough cards to replace
-                        for index in selection {
-                            self.in_play_mut().remove(index);
-                        }
-                        for _ in 0..4 {
-                            if let Some(card) = self.in_deck_mut().pop() {
-                                self.in_play_mut().push(card);
-                            }
-                        }
-                        while !selection_contains_ultraset(self.in_play()) {
-                            if self.in_deck().is_empty() {
-                                return PlayResponse::GameOver;
-                            }
-                            for _ in 0..4 {
-                                if let Some(card) = self.in_deck_mut().pop() {
-                                    self.in_play_mut().push(card);
-                                }
-                            }
-                        }
-                        return PlayResponse::ValidPlay;
-                    }
+        }
+
+        let mut response = PlayResponse::ValidPlay;
+        if self.rules().guarantee_move {
+            // Add more cards until in_play has a valid play
+            while !self.variant.contains_move(self.in_play()) {
+                if !self.active_deck.deal_refill() {
+                    response = PlayResponse::GameOver;
+                    break;
                 }
             }
+        } else if self.is_over() {
+            response = PlayResponse::GameOver;
         }
 
-        PlayResponse::InvalidPlay
+        // Replacements were dealt first, and refills were pushed past the end of replaced_by
+        let in_play = self.in_play();
+        let mut dealt: Vec<(usize, usize)> = replaced_by
+            .iter()
+            .enumerate()
+            .filter_map(|(index, order)| order.map(|order| (order, index)))
+            .collect();
+        dealt.sort_unstable();
+        let dealt = dealt
+            .into_iter()
+            .map(|(_, index)| index)
+            .chain(replaced_by.len()..in_play.len())
+            .map(|index| (index, in_play[index]))
+            .collect();
+
+        let record = Move {
+            selection: selection.iter().rev().copied().collect(),
+            removed,
+            dealt,
+        };
+        (response, record)
+    }
+
+    /// Deals one refill's worth of cards, returning the record of the deal if anything could
+    /// be dealt.
+    fn apply_deal(&mut self) -> Option<Move<V::Card>> {
+        let dealt_from = self.in_play().len();
+        if !self.active_deck.deal_refill() {
+            return None;
+        }
+        let in_play = self.in_play();
+        Some(Move {
+            selection: Vec::new(),
+            removed: Vec::new(),
+            dealt: (dealt_from..in_play.len())
+                .map(|index| (index, in_play[index]))
+                .collect(),
+        })
     }
 }
 
-impl Deck {
+impl<const N: usize> GameDeck<SetVariant<N>> {
+    /// Deals out cards with the standard rules, ensuring there is always a set. Fails if the
+    /// deck has no set at all.
+    pub fn start_set_play(deck: &Deck<N>) -> Result<Self, PlayError> {
+        Self::start(SetVariant::Set, &deck.cards)
+    }
+
+    /// Deals out cards with the given rules for a game of Set.
+    pub fn start_set_play_with_rules(deck: &Deck<N>, rules: GameRules) -> Result<Self, PlayError> {
+        Self::start_with_rules(SetVariant::Set, &deck.cards, rules)
+    }
+
+    /// Deals out cards with the standard rules, ensuring there is always an ultraset. Fails if
+    /// the deck has no ultraset at all.
+    pub fn start_ultraset_play(deck: &Deck<N>) -> Result<Self, PlayError> {
+        Self::start(SetVariant::UltraSet, &deck.cards)
+    }
+
+    /// Deals out cards with the given rules for a game of UltraSet.
+    pub fn start_ultraset_play_with_rules(
+        deck: &Deck<N>,
+        rules: GameRules,
+    ) -> Result<Self, PlayError> {
+        Self::start_with_rules(SetVariant::UltraSet, &deck.cards, rules)
+    }
+
+    /// Redraws every card of the game with `attributes`, including the cards in the deck and in
+    /// the moves that can be undone or redone. Only what the cards look like changes: their
+    /// coordinates, and so the game, stay the same.
+    pub fn redraw_cards(&mut self, attributes: &Attributes) {
+        let redraw = |card: &mut (CardCoordinates<N>, CardVisualAttr)| {
+            card.1 = visual_attributes(card.0, attributes);
+        };
+
+        let ActiveDeck {
+            in_play, in_deck, ..
+        } = &mut self.active_deck;
+        in_play
+            .iter_mut()
+            .chain(in_deck.iter_mut())
+            .for_each(redraw);
+        for record in self.history.iter_mut().chain(self.undone.iter_mut()) {
+            record.removed.iter_mut().for_each(redraw);
+            record.dealt.iter_mut().for_each(|(_, card)| redraw(card));
+        }
+    }
+
+    /// Returns the indices of every set among the cards currently in play
+    pub fn all_sets(&self) -> Vec<[usize; 3]> {
+        find_all_sets(self.in_play())
+    }
+
+    /// Returns every ultraset among the cards currently in play, grouped by ghost card
+    pub fn all_ultrasets(&self) -> Vec<UltrasetGroup<N>> {
+        find_all_ultrasets(self.in_play())
+    }
+
+    /// Returns the indices of every set or ultraset in play, depending upon the game
+    pub fn all_moves(&self) -> Vec<Vec<usize>> {
+        match self.variant {
+            SetVariant::Set => self.all_sets().iter().map(|set| set.to_vec()).collect(),
+            SetVariant::UltraSet => self
+                .all_ultrasets()
+                .iter()
+                .flat_map(|group| group.ultrasets.iter().map(|ultraset| ultraset.to_vec()))
+                .collect(),
+        }
+    }
+
+    /// Returns how many sets or ultrasets, depending upon the game, are currently in play
+    pub fn count_moves(&self) -> usize {
+        match self.variant {
+            SetVariant::Set => self.all_sets().len(),
+            SetVariant::UltraSet => self
+                .all_ultrasets()
+                .iter()
+                .map(|group| group.ultrasets.len())
+                .sum(),
+        }
+    }
+}
+
+impl<C: Copy> ActiveDeck<C> {
+    /// Deals out the initial board. If the rules guarantee a move, keeps dealing until
+    /// `contains_move` holds, and fails if it never does.
+    fn deal(
+        cards: &[C],
+        rules: GameRules,
+        contains_move: impl Fn(&[C]) -> bool,
+    ) -> Result<Self, PlayError> {
+        let mut initial_cards = rules.initial_board_size.min(cards.len());
+        if let Some(max_board_size) = rules.max_board_size {
+            initial_cards = initial_cards.min(max_board_size);
+        }
+
+        while rules.guarantee_move && !contains_move(&cards[..initial_cards]) {
+            let deal_size = rules.deal_size(initial_cards, cards.len() - initial_cards);
+            if deal_size == 0 {
+                return Err(PlayError::NoMoveAvailable);
+            }
+            initial_cards += deal_size;
+        }
+
+        Ok(Self {
+            in_play: cards[..initial_cards].to_vec(),
+            in_deck: cards[initial_cards..].to_vec(),
+            rules,
+        })
+    }
+
+    /// Deals one refill's worth of cards onto the board. Returns false if nothing could be dealt.
+    fn deal_refill(&mut self) -> bool {
+        let deal_size = self.rules.deal_size(self.in_play.len(), self.in_deck.len());
+        for _ in 0..deal_size {
+            if let Some(card) = self.in_deck.pop() {
+                self.in_play.push(card);
+            }
+        }
+        deal_size > 0
+    }
+}
+
+impl<const N: usize> Deck<N> {
     /// Creates a deck with the standard attributes and shuffles it.
     #[must_use]
     pub fn new_standard_deck() -> Self {
-        let standard_attributes = generate_standard_attributes();
-        let mut cards = Vec::new();
-
-        for num in 0..3 {
-            for color in 0..3 {
-                for shape in 0..3 {
-                    for filling in 0..3 {
-                        let coordinates = CardCoordinates::new(num, color, shape, filling);
-
-                        let actual_num = standard_attributes.numbers[num as usize];
-                        let actual_color = standard_attributes.colors[color as usize];
-                        let actual_shape = standard_attributes.shapes[shape as usize];
-                        let actual_filling = standard_attributes.fillings[filling as usize];
-
-                        let visual_attr = CardVisualAttr {
-                            num: actual_num,
-                            color: actual_color,
-                            shape: actual_shape,
-                            filling: actual_filling,
-                        };
-
-                        cards.push((coordinates, visual_attr));
-                    }
-                }
-            }
-        }
+        Self::new_standard_deck_with_rng(&mut thread_rng())
+    }
 
-        let mut rng = thread_rng();
-        cards.shuffle(&mut rng);
+    /// Creates a deck with the standard attributes, shuffled deterministically from `seed`.
+    #[must_use]
+    pub fn standard_from_seed(seed: u64) -> Self {
+        Self::new_standard_deck_with_rng(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
 
-        Self { cards }
+    /// Creates a deck with the standard attributes and shuffles it using `rng`.
+    #[must_use]
+    pub fn new_standard_deck_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::from_attributes(generate_standard_attributes(), rng)
     }
 
     /// Creates a deck with random attributes and shuffles it.
     #[must_use]
     pub fn new_random_deck() -> Self {
-        let random_attributes = generate_random_attributes();
-        let mut cards = Vec::new();
-
-        for num in 0..3 {
-            for color in 0..3 {
-                for shape in 0..3 {
-                    for filling in 0..3 {
-                        let coordinates = CardCoordinates::new(num, color, shape, filling);
-
-                        let actual_num = random_attributes.numbers[num as usize];
-                        let actual_color = random_attributes.colors[color as usize];
-                        let actual_shape = random_attributes.shapes[shape as usize];
-                        let actual_filling = random_attributes.fillings[filling as usize];
-
-                        let visual_attr = CardVisualAttr {
-                            num: actual_num,
-                            color: actual_color,
-                            shape: actual_shape,
-                            filling: actual_filling,
-                        };
-
-                        cards.push((coordinates, visual_attr));
-                    }
-                }
+        Self::new_random_deck_with_rng(&mut thread_rng())
+    }
+
+    /// Creates a deck with random attributes, where both the attributes and the shuffle are
+    /// determined by `seed`. The same seed always produces the same deck.
+    #[must_use]
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new_random_deck_with_rng(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Creates a deck with random attributes and shuffles it, drawing all randomness from `rng`.
+    #[must_use]
+    pub fn new_random_deck_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let random_attributes = generate_random_attributes_with_rng(rng);
+        Self::from_attributes(random_attributes, rng)
+    }
+
+    /// Creates a deck in which only some attributes are random, and shuffles it. Returns
+    /// `None` if the excluded values leave too few to pick from, see
+    /// `cardgen::generate_partially_random_attributes`.
+    #[must_use]
+    pub fn new_partially_random_deck(randomization: &AttributeRandomization) -> Option<Self> {
+        Self::new_partially_random_deck_with_rng(randomization, &mut thread_rng())
+    }
+
+    /// Same as `new_partially_random_deck`, drawing all randomness from `rng`.
+    #[must_use]
+    pub fn new_partially_random_deck_with_rng<R: Rng + ?Sized>(
+        randomization: &AttributeRandomization,
+        rng: &mut R,
+    ) -> Option<Self> {
+        let attributes = generate_partially_random_attributes_with_rng(randomization, rng)?;
+        Some(Self::from_attributes(attributes, rng))
+    }
+
+    /// Creates one card for every point of F_3^N, drawn with `attributes`, and shuffles them
+    /// using `rng`. This is how decks with hand-picked attributes are made.
+    #[must_use]
+    pub fn from_attributes<R: Rng + ?Sized>(attributes: Attributes, rng: &mut R) -> Self {
+        let mut cards: Vec<_> = CardCoordinates::all()
+            .map(|coordinates| (coordinates, visual_attributes(coordinates, &attributes)))
+            .collect();
+
+        cards.shuffle(rng);
+
+        Self { cards }
+    }
+
+    /// Returns the attributes the cards are drawn with, which are random in Evil Set. Values
+    /// that no card has, such as those of a missing attribute in decks with fewer than four,
+    /// are given as the standard ones.
+    pub fn attributes(&self) -> Attributes {
+        let mut attributes = generate_standard_attributes();
+        for (coordinates, visual) in &self.cards {
+            let coords = coordinates.coords();
+            let coord = |i: usize| coords.get(i).copied().unwrap_or(0) as usize;
+
+            attributes.numbers[coord(0)] = visual.num;
+            attributes.colors[coord(1)] = visual.color;
+            attributes.shapes[coord(2)] = visual.shape;
+            attributes.fillings[coord(3)] = visual.filling;
+        }
+        attributes
+    }
+}
+
+/// Maps coordinates to what is drawn on the card. Attribute `i` indexes into the `i`-th
+/// entry of `attributes` (number, color, shape, filling). Decks with fewer than four
+/// attributes draw every card with the first value of the missing attributes, and only the
+/// first four attributes of larger decks are drawn.
+pub(crate) fn visual_attributes<const N: usize>(
+    coordinates: CardCoordinates<N>,
+    attributes: &Attributes,
+) -> CardVisualAttr {
+    let coords = coordinates.coords();
+    let coord = |i: usize| coords.get(i).copied().unwrap_or(0) as usize;
+
+    CardVisualAttr {
+        num: attributes.numbers[coord(0)],
+        color: attributes.colors[coord(1)],
+        shape: attributes.shapes[coord(2)],
+        filling: attributes.fillings[coord(3)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Deck, GameDeck, PlayError, PlayResponse};
+    use crate::{GameRules, GameVariant};
+    use cardgen::{
+        generate_standard_attributes, AttributeRandomization, Attributes, SetColor, Shape,
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn same_seed_gives_same_deck() {
+        assert_eq!(
+            Deck::<4>::from_seed(42).cards,
+            Deck::<4>::from_seed(42).cards
+        );
+        assert_eq!(
+            Deck::<4>::standard_from_seed(42).cards,
+            Deck::<4>::standard_from_seed(42).cards
+        );
+        assert_ne!(
+            Deck::<4>::from_seed(42).cards,
+            Deck::<4>::from_seed(43).cards
+        );
+    }
+
+    #[test]
+    fn variant_decks_have_every_card() {
+        assert_eq!(Deck::<3>::new_standard_deck().cards.len(), 27);
+        assert_eq!(Deck::<4>::new_standard_deck().cards.len(), 81);
+        assert_eq!(Deck::<5>::new_random_deck().cards.len(), 243);
+    }
+
+    #[test]
+    fn decks_give_back_the_attributes_they_are_built_with() {
+        let themed = Attributes {
+            shapes: [Shape::Heart, Shape::Spade, Shape::Club],
+            colors: [SetColor::Blue, SetColor::Brown, SetColor::Black],
+            ..generate_standard_attributes()
+        };
+        let deck = Deck::<4>::from_attributes(themed, &mut ChaCha8Rng::seed_from_u64(1));
+        assert_eq!(deck.attributes(), themed);
+        assert!(deck
+            .cards
+            .iter()
+            .all(|(_, visual)| themed.shapes.contains(&visual.shape)));
+
+        let evil = Deck::<4>::from_seed(7);
+        let rebuilt =
+            Deck::<4>::from_attributes(evil.attributes(), &mut ChaCha8Rng::seed_from_u64(7));
+        assert!(rebuilt.cards.iter().all(|card| evil.cards.contains(card)));
+    }
+
+    #[test]
+    fn partially_random_decks_keep_the_other_attributes() {
+        let standard = generate_standard_attributes();
+        let randomization = AttributeRandomization {
+            shapes: true,
+            fillings: true,
+            excluded_shapes: vec![Shape::Diamond, Shape::Pill],
+            ..AttributeRandomization::default()
+        };
+        for seed in 0..20 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let deck = Deck::<4>::new_partially_random_deck_with_rng(&randomization, &mut rng);
+            let attributes = deck.unwrap().attributes();
+            assert_eq!(attributes.numbers, standard.numbers);
+            assert_eq!(attributes.colors, standard.colors);
+            assert!(!attributes.shapes.contains(&Shape::Diamond));
+            assert!(!attributes.shapes.contains(&Shape::Pill));
+        }
+
+        let too_few = AttributeRandomization {
+            colors: true,
+            excluded_colors: vec![
+                SetColor::Red,
+                SetColor::Green,
+                SetColor::Blue,
+                SetColor::Black,
+            ],
+            ..AttributeRandomization::default()
+        };
+        assert!(Deck::<4>::new_partially_random_deck(&too_few).is_none());
+    }
+
+    #[test]
+    fn redrawn_games_only_change_in_looks() {
+        let mut game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(4)).unwrap();
+        for _ in 0..3 {
+            let selection = game.variant().find_move(game.in_play()).unwrap();
+            game.play_selection(selection).unwrap();
+        }
+        game.undo().unwrap();
+        assert_eq!(game.plays_made(), 2);
+
+        let coordinates = |game: &GameDeck| -> Vec<_> {
+            game.in_play()
+                .iter()
+                .chain(game.in_deck())
+                .map(|card| card.0)
+                .collect()
+        };
+        let before = coordinates(&game);
+        let evil = Deck::<4>::from_seed(4).attributes();
+        game.redraw_cards(&evil);
+        assert_eq!(coordinates(&game), before);
+
+        // Cards coming back from the history are drawn the new way too
+        game.redo().unwrap();
+        game.undo().unwrap();
+        game.undo().unwrap();
+        let deck = Deck {
+            cards: game
+                .in_play()
+                .iter()
+                .chain(game.in_deck())
+                .copied()
+                .collect(),
+        };
+        assert_eq!(deck.attributes(), evil);
+    }
+
+    #[test]
+    fn junior_and_expert_games_start_with_a_set() {
+        let junior = GameDeck::start_set_play(&Deck::<3>::standard_from_seed(1)).unwrap();
+        assert!(crate::selection_contains_set(junior.in_play()));
+
+        let expert = GameDeck::start_ultraset_play(&Deck::<5>::from_seed(1)).unwrap();
+        assert!(crate::selection_contains_ultraset(expert.in_play()));
+    }
+
+    #[test]
+    fn seeded_game_plays_deterministically() {
+        let deck = Deck::<4>::standard_from_seed(7);
+        let mut first = GameDeck::start_set_play(&deck).unwrap();
+        let mut second = GameDeck::start_set_play(&deck).unwrap();
+
+        let hint = first.get_hint().unwrap();
+        let third = (0..first.in_play().len())
+            .find(|&i| {
+                !hint.contains(&i)
+                    && crate::cards::is_set(
+                        first.in_play()[hint[0]].0,
+                        first.in_play()[hint[1]].0,
+                        first.in_play()[i].0,
+                    )
+            })
+            .unwrap();
+        let selection = vec![hint[0], hint[1], third];
+
+        assert_eq!(
+            first.play_selection(selection.clone()),
+            Ok(PlayResponse::ValidPlay)
+        );
+        assert_eq!(
+            second.play_selection(selection),
+            Ok(PlayResponse::ValidPlay)
+        );
+        assert_eq!(first.in_play(), second.in_play());
+        assert_eq!(first.in_deck(), second.in_deck());
+    }
+
+    #[test]
+    fn malformed_selections_are_rejected() {
+        let mut game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(3)).unwrap();
+        let in_play = game.in_play().len();
+
+        assert_eq!(
+            game.play_selection(vec![0, 1]),
+            Err(PlayError::WrongSelectionSize {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            game.play_selection(vec![0, 1, in_play]),
+            Err(PlayError::IndexOutOfRange {
+                index: in_play,
+                in_play
+            })
+        );
+        assert_eq!(
+            game.play_selection(vec![0, 1, 1]),
+            Err(PlayError::DuplicateIndex(1))
+        );
+        assert_eq!(game.in_play().len(), in_play);
+    }
+
+    #[test]
+    fn small_decks_deal_without_panicking() {
+        let deck = Deck::<4>::standard_from_seed(5);
+
+        let empty = Deck::<4> { cards: Vec::new() };
+        assert!(matches!(
+            GameDeck::start_set_play(&empty),
+            Err(PlayError::NoMoveAvailable)
+        ));
+
+        let two_cards = Deck {
+            cards: deck.cards[..2].to_vec(),
+        };
+        assert!(matches!(
+            GameDeck::start_ultraset_play(&two_cards),
+            Err(PlayError::NoMoveAvailable)
+        ));
+
+        let seven_cards = Deck {
+            cards: deck.cards[..7].to_vec(),
+        };
+        match GameDeck::start_set_play(&seven_cards) {
+            Ok(game) => {
+                assert_eq!(game.in_play().len(), 7);
+                assert!(game.get_hint().is_ok());
+            }
+            Err(error) => {
+                assert_eq!(error, PlayError::NoMoveAvailable);
+                assert!(!crate::selection_contains_set(&seven_cards.cards));
             }
         }
+    }
 
-        let mut rng = thread_rng();
-        cards.shuffle(&mut rng);
+    #[test]
+    fn standard_game_plays_to_the_end() {
+        let mut game = GameDeck::start_set_play(&Deck::<4>::from_seed(11)).unwrap();
+        let mut sets_played = 0;
+
+        loop {
+            assert!(game.in_play().len() <= 21);
+            let [i, j, k] = game.all_sets()[0];
+            sets_played += 1;
+            match game.play_selection(vec![i, j, k]) {
+                Ok(PlayResponse::ValidPlay) => {}
+                Ok(PlayResponse::GameOver) => break,
+                other => panic!("unexpected response {:?}", other),
+            }
+        }
 
-        Self { cards }
+        assert!(game.is_over());
+        assert!(game.in_deck().is_empty());
+        assert_eq!(game.in_play().len() + 3 * sets_played, 81);
+    }
+
+    #[test]
+    fn custom_rules_control_dealing() {
+        let rules = GameRules {
+            initial_board_size: 9,
+            refill_size: 3,
+            max_board_size: Some(12),
+            guarantee_move: false,
+            collapse_extra_cards: false,
+        };
+        let mut game =
+            GameDeck::start_set_play_with_rules(&Deck::<4>::standard_from_seed(2), rules).unwrap();
+
+        assert_eq!(game.rules(), &rules);
+        assert_eq!(game.in_play().len(), 9);
+        assert_eq!(game.deal_more(), Ok(()));
+        assert_eq!(game.in_play().len(), 12);
+        assert_eq!(game.deal_more(), Err(PlayError::CannotDeal));
+        assert_eq!(game.in_play().len(), 12);
+    }
+
+    #[test]
+    fn undo_and_redo_retrace_the_game() {
+        let mut game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(5)).unwrap();
+        let state = |game: &GameDeck| (game.in_play().to_vec(), game.in_deck().to_vec());
+        let mut states = vec![state(&game)];
+
+        game.deal_more().unwrap();
+        states.push(state(&game));
+        loop {
+            let [i, j, k] = game.all_sets()[0];
+            let response = game.play_selection(vec![k, i, j]).unwrap();
+            states.push(state(&game));
+
+            let record = game.history().last().unwrap();
+            assert_eq!(record.selection, vec![i, j, k]);
+            assert!(record
+                .dealt
+                .iter()
+                .all(|&(index, card)| game.in_play()[index] == card));
+            if response == PlayResponse::GameOver {
+                break;
+            }
+        }
+        assert_eq!(game.history().len(), states.len() - 1);
+
+        for expected in states.iter().rev().skip(1) {
+            game.undo().unwrap();
+            assert_eq!(&state(&game), expected);
+        }
+        assert_eq!(game.undo(), Err(PlayError::NothingToUndo));
+
+        for expected in states.iter().skip(1) {
+            game.redo().unwrap();
+            assert_eq!(&state(&game), expected);
+        }
+        assert_eq!(game.redo(), Err(PlayError::NothingToRedo));
+
+        game.undo().unwrap();
+        let [i, j, k] = game.all_sets()[0];
+        game.play_selection(vec![i, j, k]).unwrap();
+        assert_eq!(game.redo(), Err(PlayError::NothingToRedo));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn games_round_trip_through_json() {
+        let mut game = GameDeck::start_ultraset_play(&Deck::<4>::from_seed(11)).unwrap();
+        let [i, j, k, l] = game.all_ultrasets()[0].ultrasets[0];
+        game.play_selection(vec![i, j, k, l]).unwrap();
+
+        let json = serde_json::to_string(&game).unwrap();
+        let mut restored: GameDeck = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.variant(), game.variant());
+        assert_eq!(restored.rules(), game.rules());
+        assert_eq!(restored.in_play(), game.in_play());
+        assert_eq!(restored.in_deck(), game.in_deck());
+        assert_eq!(restored.history(), game.history());
+
+        restored.undo().unwrap();
+        game.undo().unwrap();
+        assert_eq!(restored.in_play(), game.in_play());
+    }
+
+    /// A toy game: pick two numbers adding up to ten.
+    struct PairsToTen;
+
+    impl GameVariant for PairsToTen {
+        type Card = u8;
+
+        fn selection_size(&self) -> usize {
+            2
+        }
+
+        fn is_valid(&self, cards: &[u8]) -> bool {
+            matches!(cards, [a, b] if a + b == 10)
+        }
+
+        fn find_move(&self, cards: &[u8]) -> Option<Vec<usize>> {
+            (0..cards.len())
+                .flat_map(|i| (i + 1..cards.len()).map(move |j| vec![i, j]))
+                .find(|pair| cards[pair[0]] + cards[pair[1]] == 10)
+        }
+    }
+
+    #[test]
+    fn custom_variants_can_be_played() {
+        let rules = GameRules {
+            initial_board_size: 3,
+            refill_size: 1,
+            ..GameRules::set()
+        };
+        let cards = [1, 2, 3, 9, 5, 5];
+        let mut game = GameDeck::start_with_rules(PairsToTen, &cards, rules).unwrap();
+
+        assert_eq!(game.in_play(), &[1, 2, 3, 9]);
+        assert_eq!(game.get_hint(), Ok(vec![0]));
+        assert_eq!(
+            game.play_selection(vec![1, 2]),
+            Ok(PlayResponse::InvalidPlay)
+        );
+        assert_eq!(game.play_selection(vec![0, 3]), Ok(PlayResponse::ValidPlay));
+        assert_eq!(game.in_play(), &[5, 2, 3, 5]);
+        assert_eq!(game.play_selection(vec![0, 3]), Ok(PlayResponse::GameOver));
+        assert!(game.is_over());
     }
 }
@@ -0,0 +1,201 @@
+use crate::cards::complete_set;
+use crate::{CardCoordinates, GameDeck, PlayError, SetVariant};
+use cardgen::CardVisualAttr;
+
+/// How the cards of a selection compare on one attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributePattern {
+    AllSame,
+    AllDifferent,
+    /// Two cards are the same and one is different, which no set has
+    Broken,
+}
+
+/// One way of splitting four cards into two pairs, and how close the pairs come to completing
+/// to the same card
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pairing<const N: usize = 4> {
+    /// Positions in the selection of the two cards of each pair
+    pub pairs: [[usize; 2]; 2],
+    /// The card that completes each pair to a set
+    pub ghosts: [CardCoordinates<N>; 2],
+    /// Attributes on which the two ghost cards differ. The four cards are an ultraset with
+    /// this pairing exactly when there are none.
+    pub mismatched: Vec<usize>,
+}
+
+/// Why a selection is or is not a valid play
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Explanation<const N: usize = 4> {
+    /// The pattern of each attribute of three cards
+    Set(Vec<AttributePattern>),
+    /// The three ways of pairing up four cards
+    UltraSet([Pairing<N>; 3]),
+}
+
+impl<const N: usize> Explanation<N> {
+    /// Checks if the explained selection is a valid play
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Explanation::Set(patterns) => patterns
+                .iter()
+                .all(|&pattern| pattern != AttributePattern::Broken),
+            Explanation::UltraSet(pairings) => {
+                pairings.iter().any(|pairing| pairing.mismatched.is_empty())
+            }
+        }
+    }
+}
+
+/// Returns the pattern of every attribute of the selection, which is a set exactly when none
+/// of them is broken. Returns `None` if the selection does not have 3 cards.
+pub fn explain_set<const N: usize>(
+    cards_picked: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> Option<Vec<AttributePattern>> {
+    let coords = match cards_picked {
+        [card1, card2, card3] => [card1.0.coords(), card2.0.coords(), card3.0.coords()],
+        _ => return None,
+    };
+
+    let patterns = (0..N)
+        .map(|attribute| {
+            let [a, b, c] = coords.map(|card| card[attribute]);
+            if a == b && b == c {
+                AttributePattern::AllSame
+            } else if a != b && b != c && a != c {
+                AttributePattern::AllDifferent
+            } else {
+                AttributePattern::Broken
+            }
+        })
+        .collect();
+    Some(patterns)
+}
+
+/// Returns every way of pairing up the selection, which is an ultraset exactly when the pairs
+/// of one of them complete to the same card. Returns `None` if the selection does not have 4
+/// cards.
+pub fn explain_ultraset<const N: usize>(
+    cards_picked: &[(CardCoordinates<N>, CardVisualAttr)],
+) -> Option<[Pairing<N>; 3]> {
+    let cards: [CardCoordinates<N>; 4] = match cards_picked {
+        [card1, card2, card3, card4] => [card1.0, card2.0, card3.0, card4.0],
+        _ => return None,
+    };
+
+    let pairing = |pairs: [[usize; 2]; 2]| {
+        let ghosts = pairs.map(|[i, j]| complete_set(cards[i], cards[j]));
+        let (first, second) = (ghosts[0].coords(), ghosts[1].coords());
+        Pairing {
+            pairs,
+            ghosts,
+            mismatched: (0..N)
+                .filter(|&attribute| first[attribute] != second[attribute])
+                .collect(),
+        }
+    };
+    Some([
+        pairing([[0, 1], [2, 3]]),
+        pairing([[0, 2], [1, 3]]),
+        pairing([[0, 3], [1, 2]]),
+    ])
+}
+
+impl<const N: usize> SetVariant<N> {
+    /// Explains why the selected cards are or are not a valid play of this variant. Returns
+    /// `None` if the selection has the wrong number of cards.
+    pub fn explain(
+        &self,
+        cards_picked: &[(CardCoordinates<N>, CardVisualAttr)],
+    ) -> Option<Explanation<N>> {
+        match self {
+            SetVariant::Set => explain_set(cards_picked).map(Explanation::Set),
+            SetVariant::UltraSet => explain_ultraset(cards_picked).map(Explanation::UltraSet),
+        }
+    }
+}
+
+impl<const N: usize> GameDeck<SetVariant<N>> {
+    /// Explains why the cards with the selected indices are or are not a valid play. Positions
+    /// in the explanation refer to the selection sorted in increasing order. Fails like
+    /// `play_selection` on malformed selections.
+    pub fn explain_selection(&self, selection: &[usize]) -> Result<Explanation<N>, PlayError> {
+        let mut selection = selection.to_vec();
+        selection.sort_by(|a, b| b.cmp(a));
+        self.check_selection(&selection)?;
+
+        let cards: Vec<_> = selection
+            .iter()
+            .rev()
+            .map(|&index| self.in_play()[index])
+            .collect();
+        Ok(self
+            .variant()
+            .explain(&cards)
+            .expect("checked selections have the right size"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{explain_set, explain_ultraset, AttributePattern, Explanation};
+    use crate::{selection_is_set, selection_is_ultraset, CardCoordinates, Deck, GameDeck};
+    use crate::{GameVariant, PlayError};
+    use itertools::Itertools;
+
+    #[quickcheck]
+    fn explanations_agree_with_validity(seed: u64) -> bool {
+        let deck = Deck::<4>::from_seed(seed);
+        let cards = &deck.cards[..8];
+
+        let sets_agree = cards.iter().copied().combinations(3).all(|selection| {
+            let explanation = Explanation::<4>::Set(explain_set(&selection).unwrap());
+            explanation.is_valid() == selection_is_set(&selection)
+        });
+        let ultrasets_agree = cards.iter().copied().combinations(4).all(|selection| {
+            let explanation = Explanation::UltraSet(explain_ultraset(&selection).unwrap());
+            explanation.is_valid() == selection_is_ultraset(&selection)
+        });
+        sets_agree && ultrasets_agree
+    }
+
+    #[test]
+    fn explanations_point_at_the_broken_attribute() {
+        let cards = [
+            CardCoordinates::new(0, 0, 0, 0),
+            CardCoordinates::new(0, 1, 1, 1),
+            CardCoordinates::new(0, 2, 2, 1),
+        ];
+        let deck = Deck::<4>::standard_from_seed(0);
+        let selection: Vec<_> = cards
+            .iter()
+            .map(|&coords| *deck.cards.iter().find(|card| card.0 == coords).unwrap())
+            .collect();
+
+        assert_eq!(
+            explain_set(&selection),
+            Some(vec![
+                AttributePattern::AllSame,
+                AttributePattern::AllDifferent,
+                AttributePattern::AllDifferent,
+                AttributePattern::Broken,
+            ])
+        );
+        assert_eq!(explain_set(&selection[..2]), None);
+
+        let game = GameDeck::start_ultraset_play(&deck).unwrap();
+        let explanation = game.explain_selection(&[3, 0, 2, 1]).unwrap();
+        let cards: Vec<_> = game.in_play()[..4].to_vec();
+        assert_eq!(explanation.is_valid(), game.variant().is_valid(&cards));
+        assert_eq!(
+            game.explain_selection(&[0, 1, 2]),
+            Err(PlayError::WrongSelectionSize {
+                expected: 4,
+                found: 3
+            })
+        );
+    }
+}
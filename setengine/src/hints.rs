@@ -0,0 +1,179 @@
+use crate::{GameDeck, GameVariant, PlayError, SetVariant};
+
+/// How much a hint gives away, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HintLevel {
+    /// One card of a move
+    Card,
+    /// One card of a move, and an attribute of the move
+    Attribute,
+    /// Every card of a move but one, like `GameDeck::get_hint`
+    Cards,
+}
+
+/// A hint about a set or ultraset in play
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hint {
+    /// There is a move with the card at this index of `in_play`.
+    Card(usize),
+    /// There is a move with the card at index `card` of `in_play`, whose cards are all the
+    /// same on `attribute` if `all_same`, and all different otherwise.
+    Attribute {
+        card: usize,
+        attribute: usize,
+        all_same: bool,
+    },
+    /// Every card of a move but one, by index in `in_play`.
+    Cards(Vec<usize>),
+}
+
+impl HintLevel {
+    /// The level that gives away a little more, if there is one
+    pub fn next(self) -> Option<Self> {
+        match self {
+            HintLevel::Card => Some(HintLevel::Attribute),
+            HintLevel::Attribute => Some(HintLevel::Cards),
+            HintLevel::Cards => None,
+        }
+    }
+}
+
+impl<const N: usize> GameDeck<SetVariant<N>> {
+    /// Returns a hint of the given level. Hints of every level point at the same move until
+    /// the board changes, so asking for the next level only ever adds to what is known.
+    ///
+    /// Attribute hints name an attribute on which the cards of the move are all the same if
+    /// there is one, and one on which they are all different otherwise. A move with neither,
+    /// like most ultrasets, gets the hint of the `Cards` level instead.
+    pub fn get_graded_hint(&self, level: HintLevel) -> Result<Hint, PlayError> {
+        let mut selection = self
+            .variant()
+            .find_move(self.in_play())
+            .ok_or(PlayError::NoMoveAvailable)?;
+
+        let hint = match level {
+            HintLevel::Card => Hint::Card(selection[0]),
+            HintLevel::Attribute => {
+                let coords: Vec<[u8; N]> = selection
+                    .iter()
+                    .map(|&index| self.in_play()[index].0.coords())
+                    .collect();
+                let all_same = |attribute: usize| {
+                    coords
+                        .iter()
+                        .all(|card| card[attribute] == coords[0][attribute])
+                };
+                let all_different = |attribute: usize| {
+                    coords.iter().enumerate().all(|(i, card)| {
+                        coords[..i]
+                            .iter()
+                            .all(|other| other[attribute] != card[attribute])
+                    })
+                };
+
+                let same = (0..N).find(|&attribute| all_same(attribute));
+                let different = (0..N).find(|&attribute| all_different(attribute));
+                match (same, different) {
+                    (Some(attribute), _) => Hint::Attribute {
+                        card: selection[0],
+                        attribute,
+                        all_same: true,
+                    },
+                    (None, Some(attribute)) => Hint::Attribute {
+                        card: selection[0],
+                        attribute,
+                        all_same: false,
+                    },
+                    (None, None) => {
+                        selection.pop();
+                        Hint::Cards(selection)
+                    }
+                }
+            }
+            HintLevel::Cards => {
+                selection.pop();
+                Hint::Cards(selection)
+            }
+        };
+        Ok(hint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hint, HintLevel};
+    use crate::{CardCoordinates, Deck, GameDeck, PlayError};
+
+    #[quickcheck]
+    fn graded_hints_point_at_the_same_move(seed: u64, ultraset: bool) -> bool {
+        let deck = Deck::<4>::standard_from_seed(seed);
+        let game = if ultraset {
+            GameDeck::start_ultraset_play(&deck).unwrap()
+        } else {
+            GameDeck::start_set_play(&deck).unwrap()
+        };
+
+        let cards = match game.get_graded_hint(HintLevel::Cards) {
+            Ok(Hint::Cards(cards)) => cards,
+            _ => return false,
+        };
+        let card_hint = game.get_graded_hint(HintLevel::Card);
+        let attribute_hint = game.get_graded_hint(HintLevel::Attribute);
+
+        let attribute_is_consistent = match attribute_hint {
+            Ok(Hint::Attribute {
+                card,
+                attribute,
+                all_same,
+            }) => {
+                let value = |index: usize| game.in_play()[index].0.coords()[attribute];
+                let same_so_far = cards.iter().all(|&index| value(index) == value(card));
+                let different_so_far = cards
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &index)| cards[..i].iter().all(|&j| value(j) != value(index)));
+                card == cards[0] && same_so_far == all_same && (all_same || different_so_far)
+            }
+            Ok(Hint::Cards(hint)) => hint == cards,
+            _ => false,
+        };
+
+        Some(cards.clone()) == game.get_hint().ok()
+            && card_hint == Ok(Hint::Card(cards[0]))
+            && attribute_is_consistent
+    }
+
+    #[test]
+    fn attribute_hints_skip_to_cards_without_a_telling_attribute() {
+        let mut game = GameDeck::start_ultraset_play(&Deck::<4>::standard_from_seed(0)).unwrap();
+        // An ultraset whose cards are neither all the same nor all different on any attribute
+        let ultraset = [[0, 0, 0, 0], [1, 1, 1, 1], [1, 2, 0, 0], [0, 2, 1, 1]];
+        let visual = game.in_play()[0].1;
+        game.in_play_mut().clear();
+        for coords in ultraset {
+            game.in_play_mut()
+                .push((CardCoordinates::from_array(coords), visual));
+        }
+
+        let cards = game.get_graded_hint(HintLevel::Cards).unwrap();
+        assert!(matches!(cards, Hint::Cards(_)));
+        assert_eq!(game.get_graded_hint(HintLevel::Attribute), Ok(cards));
+    }
+
+    #[test]
+    fn hint_levels_go_up_to_the_full_hint() {
+        assert_eq!(HintLevel::Card.next(), Some(HintLevel::Attribute));
+        assert_eq!(HintLevel::Attribute.next(), Some(HintLevel::Cards));
+        assert_eq!(HintLevel::Cards.next(), None);
+        assert!(HintLevel::Card < HintLevel::Cards);
+
+        let mut game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(3)).unwrap();
+        game.in_play_mut().clear();
+        assert_eq!(
+            game.get_graded_hint(HintLevel::Card),
+            Err(PlayError::NoMoveAvailable)
+        );
+    }
+}
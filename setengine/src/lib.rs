@@ -1,16 +1,122 @@
+//! The rules of Set and its variants: decks, dealing, and checking plays
+//!
+//! # Notation
+//!
+//! `CardCoordinates` implement `Display` and `FromStr` as codes with one digit per attribute,
+//! e.g. `0121`, and boards are written as codes separated by spaces with `format_board` and
+//! read back with `parse_board`. What is drawn on a card is written as words, e.g.
+//! "2 red striped hearts", by `cardgen::CardVisualAttr`.
+//!
+//! # Transcripts
+//!
+//! A `Transcript` records a game so that it can be shared and replayed. It is written as
+//! tags, a blank line, and one line per play with its number, the time since the start of the
+//! game in seconds, and the cards played, or `deal` when more cards were asked for:
+//!
+//! ```text
+//! [Variant "Set"]
+//! [BoardSize "12"]
+//! [RefillSize "3"]
+//! [MaxBoardSize "-"]
+//! [GuaranteeMove "yes"]
+//! [CollapseExtraCards "yes"]
+//! [Numbers "1 2 3"]
+//! [Colors "red purple green"]
+//! [Shapes "diamond heart squiggle"]
+//! [Fillings "hollow solid checkered"]
+//! [Deck "0121 2001 1102 ..."]
+//! [Player "Ada"]
+//!
+//! 1. 4.210 0121 1102 2010
+//! 2. 9.875 deal
+//! ```
+//!
+//! The rules and the attribute tags can be left out, and any other tags are kept as they
+//! are. Tag names cannot be empty or have whitespace or `]` in them, and each of the tags
+//! above but `Player` is given at most once. `Deck` lists every card in the order of
+//! `Deck::cards`, and the attribute tags give what the values 0, 1 and 2 of each attribute are
+//! drawn as. Tag values escape `"`, `\`, line feeds and carriage returns with a backslash, as
+//! `\"`, `\\`, `\n` and `\r`.
+//!
+//! # Serialization
+//!
+//! With the `serde` feature enabled, the cards, decks, rules, variants and games implement
+//! `Serialize` and `Deserialize`, which also enables the feature in `cardgen`. The format
+//! below is stable, and is shown as JSON.
+//!
+//! - `CardCoordinates` and `QuadCoordinates` are arrays of attribute values, e.g.
+//!   `[0, 1, 2, 1]`. Deserializing fails on the wrong number of attributes or an out of range
+//!   value.
+//! - `ProjectiveCard` is the bitmask of its dots, e.g. `37`.
+//! - `CardVisualAttr` is an object of variant names, e.g.
+//!   `{"num": "One", "color": "Red", "shape": "Squiggle", "filling": "Solid"}`.
+//! - `Deck`, `QuadDeck` and `ProjectiveDeck` are `{"cards": [...]}`. Cards of `Deck` and
+//!   `QuadDeck` are pairs `[coordinates, visual attributes]`.
+//! - `GameRules` is an object with the same field names as the struct.
+//! - `SetVariant` is `"Set"` or `"UltraSet"`, and `Quads` and `ProjectiveSet` are `null`.
+//! - `GameDeck` is `{"variant": ..., "active_deck": {"in_play": [...], "in_deck": [...],
+//!   "rules": ...}, "history": [...], "undone": [...]}`. The next card to be dealt is the last
+//!   one of `in_deck`.
+//! - `Move` is `{"selection": [...], "removed": [...], "dealt": [[index, card], ...]}`.
+//! - `Puzzle` is `{"board": [...], "sets": [[i, j, k], ...]}` with cards like those of `Deck`,
+//!   and `PuzzleDate` is `{"year": 2022, "month": 3, "day": 14}`.
+//! - `Session` is `{"game": ..., "scoring": ..., "players": [...], "claims": [...]}`, and
+//!   `ScoringRules`, `Player`, `Claim` and `Standing` are objects with the same field names as
+//!   the structs. Durations are `{"secs": 3, "nanos": 0}`.
+//! - `Explanation` is `{"Set": ["AllSame", "Broken", ...]}` or `{"UltraSet": [pairing, ...]}`,
+//!   where a `Pairing` is `{"pairs": [[0, 1], [2, 3]], "ghosts": [...], "mismatched": [...]}`.
+//! - `Transcript` and `TimedPlay` are objects with the same field names as the structs, and
+//!   `Attributes` is `{"numbers": ["One", "Two", "Three"], "colors": [...], ...}`.
+//! - `Solution` is `{"line": [[0, 4, 7], [], ...], "cards_left": 0, "optimal": true}`.
+//! - `HintLevel` is a string like `"Attribute"`, and `Hint` is e.g. `{"Card": 4}` or
+//!   `{"Attribute": {"card": 4, "attribute": 1, "all_same": true}}`.
+//! - `PlayResponse`, `PlayError` and `SessionError` use serde's default representation of
+//!   enums, e.g. `"ValidPlay"` or `{"DuplicateIndex": 3}`.
+
 #![forbid(unsafe_code)]
 #![cfg_attr(not(debug_assertions), deny(warnings))] // Forbid warnings in release builds
 #![warn(rust_2018_idioms)]
 #![warn(clippy::all)]
 
+mod affine;
+mod bot;
 mod cards;
 mod deck;
+mod explain;
+mod hints;
+mod notation;
+mod projective;
+mod puzzle;
+mod quads;
+mod rules;
+mod session;
+pub mod sim;
+mod solver;
+mod tables;
+mod transcript;
+mod variant;
 
+pub use affine::{canonical_board, canonical_transform, AffineTransform};
+pub use bot::{Bot, BotMove, SkillModel};
 pub use cards::{
-    selection_contains_set, selection_contains_ultraset, selection_is_set, selection_is_ultraset,
-    CardCoordinates,
+    find_all_sets, find_all_ultrasets, selection_contains_set, selection_contains_ultraset,
+    selection_is_set, selection_is_ultraset, CardCoordinates, UltrasetGroup,
+};
+pub use deck::{Deck, GameDeck, Move, PlayError, PlayResponse};
+pub use explain::{explain_set, explain_ultraset, AttributePattern, Explanation, Pairing};
+pub use hints::{Hint, HintLevel};
+pub use notation::{format_board, parse_board, ParseCardError};
+pub use projective::{
+    selection_contains_projective_set, selection_is_projective_set, ProjectiveCard, ProjectiveDeck,
+    ProjectiveSet,
 };
-pub use deck::{Deck, GameDeck, PlayResponse};
+pub use puzzle::{Puzzle, PuzzleDate, PUZZLE_BOARD_SIZE};
+pub use quads::{selection_contains_quad, selection_is_quad, QuadCoordinates, QuadDeck, Quads};
+pub use rules::GameRules;
+pub use session::{Claim, Player, ScoringRules, Session, SessionError, Standing};
+pub use solver::Solution;
+pub use transcript::{TimedPlay, Transcript, TranscriptError};
+pub use variant::{GameVariant, SetVariant};
 
 #[cfg(test)]
 extern crate quickcheck;
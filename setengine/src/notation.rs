@@ -0,0 +1,131 @@
+use crate::CardCoordinates;
+use std::fmt;
+use std::str::FromStr;
+
+/// The error returned when a card code cannot be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCardError {
+    /// The code does not have one digit for each attribute.
+    WrongLength { expected: usize, found: usize },
+    /// The code has a character other than 0, 1 and 2.
+    InvalidDigit(char),
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCardError::WrongLength { expected, found } => {
+                write!(f, "expected a code of {} digits, found {}", expected, found)
+            }
+            ParseCardError::InvalidDigit(digit) => {
+                write!(
+                    f,
+                    "'{}' is not an attribute value, which are 0, 1 and 2",
+                    digit
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+/// Written as the value of every attribute, in order, e.g. `0121`
+impl<const N: usize> fmt::Display for CardCoordinates<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for coord in self.coords() {
+            write!(f, "{}", coord)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> FromStr for CardCoordinates<N> {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let found = s.chars().count();
+        if found != N {
+            return Err(ParseCardError::WrongLength { expected: N, found });
+        }
+
+        let mut coords = [0; N];
+        for (coord, digit) in coords.iter_mut().zip(s.chars()) {
+            *coord = match digit {
+                '0'..='2' => digit as u8 - b'0',
+                _ => return Err(ParseCardError::InvalidDigit(digit)),
+            };
+        }
+        Ok(Self::from_array(coords))
+    }
+}
+
+/// Reads a board from a line of card codes separated by spaces or commas, e.g.
+/// `"0000 1111, 2222"`.
+pub fn parse_board<const N: usize>(line: &str) -> Result<Vec<CardCoordinates<N>>, ParseCardError> {
+    line.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|code| !code.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Writes a board as a line of card codes separated by spaces, which `parse_board` reads back.
+pub fn format_board<const N: usize>(cards: &[CardCoordinates<N>]) -> String {
+    cards
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_board, parse_board, ParseCardError};
+    use crate::{CardCoordinates, Deck};
+    use cardgen::CardVisualAttr;
+
+    #[quickcheck]
+    fn boards_round_trip_through_codes(seed: u64) -> bool {
+        let deck = Deck::<4>::from_seed(seed);
+        let cards: Vec<CardCoordinates> = deck.cards[..12].iter().map(|card| card.0).collect();
+        let visuals_round_trip = deck
+            .cards
+            .iter()
+            .all(|(_, visual)| visual.to_string().parse::<CardVisualAttr>() == Ok(*visual));
+
+        parse_board(&format_board(&cards)) == Ok(cards) && visuals_round_trip
+    }
+
+    #[test]
+    fn codes_are_read_and_written() {
+        let card = CardCoordinates::new(0, 1, 2, 1);
+        assert_eq!(card.to_string(), "0121");
+        assert_eq!("0121".parse(), Ok(card));
+        assert_eq!(
+            "012".parse::<CardCoordinates>(),
+            Err(ParseCardError::WrongLength {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            "0131".parse::<CardCoordinates>(),
+            Err(ParseCardError::InvalidDigit('3'))
+        );
+        assert_eq!(
+            parse_board::<3>("000, 111\t222 ,"),
+            Ok(vec![
+                CardCoordinates::from_array([0, 0, 0]),
+                CardCoordinates::from_array([1, 1, 1]),
+                CardCoordinates::from_array([2, 2, 2]),
+            ])
+        );
+
+        let visual: CardVisualAttr = "2 Red striped HEARTS".parse().unwrap();
+        assert_eq!(visual.to_string(), "2 red striped hearts");
+        let visual: CardVisualAttr = "1 green hollow diamond".parse().unwrap();
+        assert_eq!(visual.to_string(), "1 green hollow diamond");
+        assert!("2 red striped".parse::<CardVisualAttr>().is_err());
+        assert!("2 pink striped hearts".parse::<CardVisualAttr>().is_err());
+    }
+}
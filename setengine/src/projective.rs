@@ -0,0 +1,268 @@
+use crate::{GameDeck, GameRules, GameVariant, PlayError};
+use cardgen::MAX_DOTS;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+/// A Projective Set card: a non-zero vector in F_2^N, whose `i`th coordinate says whether the
+/// `i`th dot is on the card. The standard deck has `N = 6` dots and 63 cards.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProjectiveCard<const N: usize = 6> {
+    bits: u8,
+}
+
+/// A shuffled deck of Projective Set cards with `N` dots
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProjectiveDeck<const N: usize = 6> {
+    pub cards: Vec<ProjectiveCard<N>>,
+}
+
+/// Projective Set: find any collection of cards on which every dot appears an even number of
+/// times. The board holds `N + 1` cards, which always contain such a collection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProjectiveSet<const N: usize = 6>;
+
+/// Checks if selection is a projective set: it is non-empty, and every dot appears on an even
+/// number of the selected cards.
+pub fn selection_is_projective_set<const N: usize>(cards_picked: &[ProjectiveCard<N>]) -> bool {
+    !cards_picked.is_empty() && cards_picked.iter().fold(0, |sum, card| sum ^ card.bits) == 0
+}
+
+/// Checks if selection contains a projective set.
+pub fn selection_contains_projective_set<const N: usize>(
+    cards_in_play: &[ProjectiveCard<N>],
+) -> bool {
+    find_projective_set(cards_in_play).is_some()
+}
+
+/// Returns the indices of a projective set in `cards` in increasing order, if there is one.
+///
+/// This is Gaussian elimination over F_2: each card is reduced against a basis of the cards
+/// before it, keeping track of which cards were added up along the way. A card that reduces to
+/// zero closes a projective set. Any `N + 1` cards are linearly dependent, so the search stops
+/// within the first `N + 1` cards.
+pub(crate) fn find_projective_set<const N: usize>(
+    cards: &[ProjectiveCard<N>],
+) -> Option<Vec<usize>> {
+    // basis[d] is a reduced vector whose highest dot is d, and the cards summing to it
+    let mut basis: [Option<(u8, u64)>; MAX_DOTS] = [None; MAX_DOTS];
+
+    for (index, card) in cards.iter().enumerate() {
+        let (mut sum, mut used) = (card.bits, 1u64 << index);
+        while sum != 0 {
+            let highest_dot = 7 - sum.leading_zeros() as usize;
+            match basis[highest_dot] {
+                Some((vector, cards_used)) => {
+                    sum ^= vector;
+                    used ^= cards_used;
+                }
+                None => {
+                    basis[highest_dot] = Some((sum, used));
+                    break;
+                }
+            }
+        }
+
+        if sum == 0 {
+            return Some((0..=index).filter(|i| used & (1 << i) != 0).collect());
+        }
+    }
+    None
+}
+
+impl<const N: usize> ProjectiveCard<N> {
+    /// Number of distinct cards with `N` dots, i.e. the number of non-zero vectors in F_2^N.
+    pub const DECK_SIZE: usize = {
+        assert!(N <= MAX_DOTS, "cards have at most six dots");
+        (1 << N) - 1
+    };
+
+    /// Creates a card from a bitmask, with bit `i` set if the `i`th dot is on the card. Panics
+    /// if no dot is set, or a dot beyond the `N`th is.
+    pub fn from_bits(bits: u8) -> Self {
+        assert!(
+            bits != 0 && bits as usize <= Self::DECK_SIZE,
+            "card bits out of range"
+        );
+        Self { bits }
+    }
+
+    /// Creates a card from the dots on it. Panics if there are none.
+    pub fn from_dots(dots: [bool; N]) -> Self {
+        let bits = dots
+            .iter()
+            .rev()
+            .fold(0, |bits, &dot| 2 * bits + u8::from(dot));
+        Self::from_bits(bits)
+    }
+
+    /// The bitmask of this card, with bit `i` set if the `i`th dot is on the card.
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// Returns whether each dot is on the card, in the form `cardgen::render_dot_card` takes.
+    pub fn dots(&self) -> [bool; N] {
+        std::array::from_fn(|i| self.bits & (1 << i) != 0)
+    }
+
+    /// Iterates over every card with `N` dots, in increasing order of bitmask.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (1..=Self::DECK_SIZE).map(|bits| Self::from_bits(bits as u8))
+    }
+}
+
+impl<const N: usize> fmt::Debug for ProjectiveCard<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProjectiveCard")
+            .field("dots", &self.dots())
+            .finish()
+    }
+}
+
+/// Serialized as the bitmask of dots, e.g. `37` for the first, third and sixth dots.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for ProjectiveCard<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.bits)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for ProjectiveCard<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let bits: u8 = serde::Deserialize::deserialize(deserializer)?;
+        if bits == 0 || bits as usize > Self::DECK_SIZE {
+            return Err(D::Error::custom(format!(
+                "card bits must be between 1 and {}",
+                Self::DECK_SIZE
+            )));
+        }
+        Ok(Self { bits })
+    }
+}
+
+impl<const N: usize> ProjectiveDeck<N> {
+    /// Creates a shuffled deck of every card with `N` dots
+    pub fn new_deck() -> Self {
+        Self::new_deck_with_rng(&mut thread_rng())
+    }
+
+    /// Creates a shuffled deck which is the same for the same seed
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new_deck_with_rng(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Creates a deck shuffled with the given random number generator
+    pub fn new_deck_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut cards: Vec<ProjectiveCard<N>> = ProjectiveCard::all().collect();
+        cards.shuffle(rng);
+        Self { cards }
+    }
+}
+
+impl<const N: usize> GameVariant for ProjectiveSet<N> {
+    type Card = ProjectiveCard<N>;
+
+    fn selection_size(&self) -> usize {
+        N + 1
+    }
+
+    fn min_selection_size(&self) -> usize {
+        // Two distinct cards never share all their dots
+        3
+    }
+
+    fn is_valid(&self, cards: &[Self::Card]) -> bool {
+        selection_is_projective_set(cards)
+    }
+
+    fn find_move(&self, cards: &[Self::Card]) -> Option<Vec<usize>> {
+        find_projective_set(cards)
+    }
+
+    fn default_rules(&self) -> GameRules {
+        GameRules {
+            initial_board_size: N + 1,
+            refill_size: 1,
+            ..GameRules::set()
+        }
+    }
+}
+
+impl<const N: usize> GameDeck<ProjectiveSet<N>> {
+    /// Deals out `N + 1` cards for a game of Projective Set.
+    pub fn start_projective_play(deck: &ProjectiveDeck<N>) -> Result<Self, PlayError> {
+        Self::start(ProjectiveSet, &deck.cards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_projective_set, selection_is_projective_set, ProjectiveCard, ProjectiveDeck};
+    use crate::{GameDeck, PlayResponse};
+    use std::collections::HashSet;
+
+    #[test]
+    fn deck_has_every_non_zero_vector() {
+        let deck = ProjectiveDeck::<6>::from_seed(0);
+        let cards: HashSet<u8> = deck.cards.iter().map(|card| card.bits()).collect();
+
+        assert_eq!(deck.cards.len(), 63);
+        assert_eq!(cards, (1..64).collect());
+        assert!(selection_is_projective_set(&deck.cards));
+        assert_eq!(
+            ProjectiveCard::<6>::from_dots([true, false, true, false, false, true]).bits(),
+            0b100101
+        );
+    }
+
+    #[quickcheck]
+    fn find_projective_set_agrees_with_brute_force(seed: u64, size: u8) -> bool {
+        let deck = ProjectiveDeck::<6>::from_seed(seed);
+        let cards = &deck.cards[..(size % 8) as usize];
+
+        let brute_force = (1..1u32 << cards.len()).any(|subset| {
+            let picked: Vec<ProjectiveCard> = (0..cards.len())
+                .filter(|i| subset & (1 << i) != 0)
+                .map(|i| cards[i])
+                .collect();
+            selection_is_projective_set(&picked)
+        });
+
+        match find_projective_set(cards) {
+            Some(indices) => {
+                let picked: Vec<ProjectiveCard> = indices.iter().map(|&i| cards[i]).collect();
+                brute_force && selection_is_projective_set(&picked)
+            }
+            None => !brute_force,
+        }
+    }
+
+    #[test]
+    fn projective_game_plays_to_the_end() {
+        let deck = ProjectiveDeck::<6>::from_seed(7);
+        let mut game = GameDeck::start_projective_play(&deck).unwrap();
+        assert_eq!(game.in_play().len(), 7);
+
+        let mut cards_played = 0;
+        loop {
+            let play = find_projective_set(game.in_play()).unwrap();
+            cards_played += play.len();
+            match game.play_selection(play) {
+                Ok(PlayResponse::ValidPlay) => assert!(game.in_play().len() <= 7),
+                Ok(PlayResponse::GameOver) => break,
+                other => panic!("unexpected response {:?}", other),
+            }
+        }
+
+        // The whole deck sums to zero, so whatever is left over would be another play
+        assert!(game.is_over());
+        assert!(game.in_play().is_empty() && game.in_deck().is_empty());
+        assert_eq!(cards_played, 63);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{find_all_sets, CardCoordinates, Deck};
+use cardgen::CardVisualAttr;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+/// Number of cards on a puzzle board
+pub const PUZZLE_BOARD_SIZE: usize = 12;
+
+/// Number of cards swapped in and out of a board before giving up on a puzzle
+const MAX_STEPS: usize = 10_000;
+
+/// A day of the Gregorian calendar, which picks the daily puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PuzzleDate {
+    pub year: i32,
+    /// From 1 to 12
+    pub month: u32,
+    /// From 1 to 31
+    pub day: u32,
+}
+
+/// A board of `PUZZLE_BOARD_SIZE` cards, and every set on it. Unlike in a game, the cards stay
+/// on the board and the player has to find all of the sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Puzzle<const N: usize = 4> {
+    pub board: Vec<(CardCoordinates<N>, CardVisualAttr)>,
+    /// Indices of the cards of each set on the board, each in increasing order
+    pub sets: Vec<[usize; 3]>,
+}
+
+impl PuzzleDate {
+    /// Returns the date `days` days after 1970-01-01, which is what dividing a Unix timestamp
+    /// by the length of a day gives.
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        // Days since 0000-03-01, so that leap days fall at the end of the year
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + 400 * era + i64::from(month <= 2);
+
+        Self {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// Seed from which the puzzle of the day is generated
+    pub fn seed(&self) -> u64 {
+        ((self.year as u64) << 16) | (u64::from(self.month) << 8) | u64::from(self.day)
+    }
+}
+
+impl fmt::Display for PuzzleDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl<const N: usize> Puzzle<N> {
+    /// Generates the puzzle of `date`, which has exactly `sets` sets. With `evil`, the cards
+    /// get a random attribute mapping like in Evil Set. Everyone asking for the puzzle of the
+    /// same day with the same options gets the same board.
+    pub fn daily(date: PuzzleDate, sets: usize, evil: bool) -> Option<Self> {
+        Self::from_seed(sets, evil, date.seed())
+    }
+
+    /// Generates a puzzle with exactly `sets` sets from `seed`
+    pub fn from_seed(sets: usize, evil: bool, seed: u64) -> Option<Self> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        Self::generate_with_rng(sets, evil, &mut rng)
+    }
+
+    /// Generates a puzzle with exactly `sets` sets. Returns `None` if no such board was found,
+    /// which happens when `sets` is more than a board of `PUZZLE_BOARD_SIZE` cards can have.
+    pub fn generate_with_rng<R: Rng + ?Sized>(
+        sets: usize,
+        evil: bool,
+        rng: &mut R,
+    ) -> Option<Self> {
+        let mut cards = if evil {
+            Deck::<N>::new_random_deck_with_rng(rng).cards
+        } else {
+            Deck::<N>::new_standard_deck_with_rng(rng).cards
+        };
+        if cards.len() <= PUZZLE_BOARD_SIZE {
+            return None;
+        }
+
+        // Swaps cards between the board and the rest of the deck, keeping every swap that does
+        // not take the number of sets further away from the target
+        let distance = |cards: &[(CardCoordinates<N>, CardVisualAttr)]| {
+            find_all_sets(&cards[..PUZZLE_BOARD_SIZE])
+                .len()
+                .abs_diff(sets)
+        };
+        let mut current = distance(&cards);
+        for _ in 0..MAX_STEPS {
+            if current == 0 {
+                break;
+            }
+            let on_board = rng.gen_range(0..PUZZLE_BOARD_SIZE);
+            let off_board = rng.gen_range(PUZZLE_BOARD_SIZE..cards.len());
+            cards.swap(on_board, off_board);
+
+            let new = distance(&cards);
+            if new <= current {
+                current = new;
+            } else {
+                cards.swap(on_board, off_board);
+            }
+        }
+
+        if current != 0 {
+            return None;
+        }
+        cards.truncate(PUZZLE_BOARD_SIZE);
+        Some(Self {
+            sets: find_all_sets(&cards),
+            board: cards,
+        })
+    }
+
+    /// Returns the index in `sets` of the set made of the selected cards, if they make one
+    pub fn find(&self, selection: &[usize]) -> Option<usize> {
+        let mut selection = selection.to_vec();
+        selection.sort_unstable();
+        self.sets.iter().position(|set| set[..] == selection[..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Puzzle, PuzzleDate, PUZZLE_BOARD_SIZE};
+
+    #[test]
+    fn puzzles_have_exactly_the_requested_sets() {
+        for sets in [0, 1, 6, 10] {
+            for evil in [false, true] {
+                let puzzle = Puzzle::<4>::from_seed(sets, evil, sets as u64).unwrap();
+                assert_eq!(puzzle.board.len(), PUZZLE_BOARD_SIZE);
+                assert_eq!(puzzle.sets.len(), sets);
+                for set in &puzzle.sets {
+                    assert_eq!(puzzle.find(&[set[2], set[0], set[1]]), puzzle.find(set));
+                    assert!(puzzle.find(set).is_some());
+                }
+            }
+        }
+        assert_eq!(Puzzle::<4>::from_seed(30, false, 0), None);
+    }
+
+    #[test]
+    fn daily_puzzles_depend_only_on_the_date() {
+        let today = PuzzleDate::from_days_since_epoch(20_744);
+        assert_eq!(today.to_string(), "2026-10-18");
+        assert_eq!(
+            PuzzleDate::from_days_since_epoch(0).to_string(),
+            "1970-01-01"
+        );
+        assert_eq!(
+            PuzzleDate::from_days_since_epoch(11_016).to_string(),
+            "2000-02-29"
+        );
+
+        let tomorrow = PuzzleDate::from_days_since_epoch(20_745);
+        assert_eq!(
+            Puzzle::<4>::daily(today, 6, true),
+            Puzzle::<4>::daily(today, 6, true)
+        );
+        assert_ne!(
+            Puzzle::<4>::daily(today, 6, true),
+            Puzzle::<4>::daily(tomorrow, 6, true)
+        );
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{GameDeck, GameRules, GameVariant, PlayError};
+use cardgen::{
+    generate_random_quad_attributes_with_rng, generate_standard_quad_attributes, CardVisualAttr,
+    QuadAttributes,
+};
+use itertools::Itertools;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+/// Largest number of attributes a Quads card can have.
+const MAX_ATTRIBUTES: usize = 4;
+/// Number of cards in a deck of Quads with the most attributes.
+const MAX_CARDS: usize = 256;
+
+/// Coordinates of a Quads card: `N` attributes taking four values each. The standard deck has
+/// `N = 4` attributes and 256 cards.
+///
+/// Each attribute is stored in two bits of a single index, with the first attribute in the
+/// most significant bits. Four values of an attribute are all the same, all different, or two
+/// pairs exactly when their bitwise XOR is zero, so four cards form a quad exactly when their
+/// indices XOR to zero.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuadCoordinates<const N: usize = 4> {
+    index: u8,
+}
+
+/// A shuffled deck of Quads cards with `N` attributes
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadDeck<const N: usize = 4> {
+    pub cards: Vec<(QuadCoordinates<N>, CardVisualAttr)>,
+}
+
+/// Quads: find four cards on which every attribute is all the same, all different, or two
+/// pairs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quads<const N: usize = 4>;
+
+/// Checks if selection is a quad. A selection that does not have exactly 4 cards is not a quad.
+pub fn selection_is_quad<const N: usize>(
+    cards_picked: &[(QuadCoordinates<N>, CardVisualAttr)],
+) -> bool {
+    match cards_picked {
+        [card1, card2, card3, card4] => is_quad(card1.0, card2.0, card3.0, card4.0),
+        _ => false,
+    }
+}
+
+/// Checks if selection contains a quad.
+pub fn selection_contains_quad<const N: usize>(
+    cards_in_play: &[(QuadCoordinates<N>, CardVisualAttr)],
+) -> bool {
+    find_quad(cards_in_play).is_some()
+}
+
+/// Returns the indices of a quad in `cards`, if there is one. Pairs are bucketed by the XOR of
+/// their indices; two distinct pairs in the same bucket cannot share a card, so the first
+/// bucket to receive a second pair gives a quad.
+pub(crate) fn find_quad<const N: usize>(
+    cards: &[(QuadCoordinates<N>, CardVisualAttr)],
+) -> Option<[usize; 4]> {
+    let mut sums: [Option<(usize, usize)>; MAX_CARDS] = [None; MAX_CARDS];
+
+    for (i, j) in (0..cards.len()).tuple_combinations() {
+        let sum = cards[i].0.index() ^ cards[j].0.index();
+        match sums[sum] {
+            Some((k, l)) => return Some([k, l, i, j]),
+            None => sums[sum] = Some((i, j)),
+        }
+    }
+    None
+}
+
+pub(crate) fn is_quad<const N: usize>(
+    card1: QuadCoordinates<N>,
+    card2: QuadCoordinates<N>,
+    card3: QuadCoordinates<N>,
+    card4: QuadCoordinates<N>,
+) -> bool {
+    card1.index ^ card2.index ^ card3.index ^ card4.index == 0
+}
+
+impl<const N: usize> QuadCoordinates<N> {
+    /// Number of distinct cards with `N` attributes, i.e. the size of F_4^N.
+    pub const DECK_SIZE: usize = {
+        assert!(
+            N <= MAX_ATTRIBUTES,
+            "quads cards have at most four attributes"
+        );
+        4usize.pow(N as u32)
+    };
+
+    /// Creates a coordinate from an array of u8s, reducing each entry mod 4.
+    pub fn from_array(coords: [u8; N]) -> Self {
+        let index = coords
+            .iter()
+            .fold(0, |index, coord| 4 * index + (coord % 4) as usize);
+        Self::from_index(index)
+    }
+
+    /// Creates a coordinate from its index in `0..DECK_SIZE`, the base 4 number whose digits
+    /// are the attributes. Panics if the index is out of range.
+    pub fn from_index(index: usize) -> Self {
+        assert!(index < Self::DECK_SIZE, "card index out of range");
+        Self { index: index as u8 }
+    }
+
+    /// The index of this card in `0..DECK_SIZE`.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// Returns the value of every attribute, each in `0..4`.
+    pub fn coords(&self) -> [u8; N] {
+        let mut index = self.index;
+        let mut coords = [0; N];
+        for coord in coords.iter_mut().rev() {
+            *coord = index % 4;
+            index /= 4;
+        }
+        coords
+    }
+
+    /// Iterates over every card with `N` attributes, with the last attribute varying fastest.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..Self::DECK_SIZE).map(Self::from_index)
+    }
+}
+
+impl<const N: usize> fmt::Debug for QuadCoordinates<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuadCoordinates")
+            .field("coords", &self.coords())
+            .finish()
+    }
+}
+
+/// Serialized as the array of attribute values, e.g. `[0, 1, 2, 1]`.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for QuadCoordinates<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.coords().as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for QuadCoordinates<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let coords: Vec<u8> = serde::Deserialize::deserialize(deserializer)?;
+        let coords: [u8; N] = coords.try_into().map_err(|coords: Vec<u8>| {
+            D::Error::invalid_length(coords.len(), &format!("{} attributes", N).as_str())
+        })?;
+        if coords.iter().any(|&coord| coord >= 4) {
+            return Err(D::Error::custom("attribute values must be less than 4"));
+        }
+        Ok(Self::from_array(coords))
+    }
+}
+
+impl<const N: usize> QuadDeck<N> {
+    /// Creates a deck with the first four values of every attribute and shuffles it.
+    #[must_use]
+    pub fn new_standard_deck() -> Self {
+        Self::new_standard_deck_with_rng(&mut thread_rng())
+    }
+
+    /// Creates a deck with the standard attributes, shuffled deterministically from `seed`.
+    #[must_use]
+    pub fn standard_from_seed(seed: u64) -> Self {
+        Self::new_standard_deck_with_rng(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Creates a deck with the standard attributes and shuffles it using `rng`.
+    #[must_use]
+    pub fn new_standard_deck_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::with_attributes(&generate_standard_quad_attributes(), rng)
+    }
+
+    /// Creates a deck with four of the six values of every attribute picked at random, and
+    /// shuffles it.
+    #[must_use]
+    pub fn new_random_deck() -> Self {
+        Self::new_random_deck_with_rng(&mut thread_rng())
+    }
+
+    /// Creates a deck with random attributes, where both the attributes and the shuffle are
+    /// determined by `seed`. The same seed always produces the same deck.
+    #[must_use]
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new_random_deck_with_rng(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Creates a deck with random attributes and shuffles it, drawing all randomness from `rng`.
+    #[must_use]
+    pub fn new_random_deck_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let random_attributes = generate_random_quad_attributes_with_rng(rng);
+        Self::with_attributes(&random_attributes, rng)
+    }
+
+    /// Creates one card for every point of F_4^N and shuffles them.
+    fn with_attributes<R: Rng + ?Sized>(attributes: &QuadAttributes, rng: &mut R) -> Self {
+        let mut cards: Vec<_> = QuadCoordinates::all()
+            .map(|coordinates| (coordinates, visual_attributes(coordinates, attributes)))
+            .collect();
+
+        cards.shuffle(rng);
+
+        Self { cards }
+    }
+}
+
+/// Maps coordinates to what is drawn on the card, the same way as for a deck of Set.
+fn visual_attributes<const N: usize>(
+    coordinates: QuadCoordinates<N>,
+    attributes: &QuadAttributes,
+) -> CardVisualAttr {
+    let coords = coordinates.coords();
+    let coord = |i: usize| coords.get(i).copied().unwrap_or(0) as usize;
+
+    CardVisualAttr {
+        num: attributes.numbers[coord(0)],
+        color: attributes.colors[coord(1)],
+        shape: attributes.shapes[coord(2)],
+        filling: attributes.fillings[coord(3)],
+    }
+}
+
+impl<const N: usize> GameVariant for Quads<N> {
+    type Card = (QuadCoordinates<N>, CardVisualAttr);
+
+    fn selection_size(&self) -> usize {
+        4
+    }
+
+    fn is_valid(&self, cards: &[Self::Card]) -> bool {
+        selection_is_quad(cards)
+    }
+
+    fn find_move(&self, cards: &[Self::Card]) -> Option<Vec<usize>> {
+        find_quad(cards).map(|indices| indices.to_vec())
+    }
+
+    fn default_rules(&self) -> GameRules {
+        // Twelve cards, four more whenever there is no quad
+        GameRules {
+            refill_size: 4,
+            ..GameRules::set()
+        }
+    }
+}
+
+impl<const N: usize> GameDeck<Quads<N>> {
+    /// Deals out cards with the standard rules, ensuring there is always a quad. Fails if the
+    /// deck has no quad at all.
+    pub fn start_quads_play(deck: &QuadDeck<N>) -> Result<Self, PlayError> {
+        Self::start(Quads, &deck.cards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_quad, is_quad, QuadCoordinates, QuadDeck};
+    use crate::{GameDeck, PlayResponse};
+    use itertools::Itertools;
+    use quickcheck::{Arbitrary, Gen};
+    use std::collections::HashSet;
+
+    impl Arbitrary for QuadCoordinates {
+        fn arbitrary(g: &mut Gen) -> Self {
+            QuadCoordinates::from_array([
+                u8::arbitrary(g),
+                u8::arbitrary(g),
+                u8::arbitrary(g),
+                u8::arbitrary(g),
+            ])
+        }
+    }
+
+    #[quickcheck]
+    fn is_quad_matches_attributewise_rule(
+        card1: QuadCoordinates,
+        card2: QuadCoordinates,
+        card3: QuadCoordinates,
+        card4: QuadCoordinates,
+    ) -> bool {
+        let (c1, c2, c3, c4) = (
+            card1.coords(),
+            card2.coords(),
+            card3.coords(),
+            card4.coords(),
+        );
+        let attributewise = (0..4).all(|i| {
+            let mut values = [c1[i], c2[i], c3[i], c4[i]];
+            values.sort_unstable();
+            let all_same = values[0] == values[3];
+            let all_different = values == [0, 1, 2, 3];
+            let two_pairs = values[0] == values[1] && values[2] == values[3];
+            all_same || all_different || two_pairs
+        });
+
+        is_quad(card1, card2, card3, card4) == attributewise
+    }
+
+    #[test]
+    fn decks_have_every_card_with_four_values_per_attribute() {
+        let standard = QuadDeck::<4>::standard_from_seed(0);
+        let coordinates: HashSet<QuadCoordinates> =
+            standard.cards.iter().map(|card| card.0).collect();
+        assert_eq!(coordinates.len(), 256);
+
+        let evil = QuadDeck::<4>::from_seed(0);
+        let colors: HashSet<_> = evil.cards.iter().map(|card| card.1.color).collect();
+        let shapes: HashSet<_> = evil.cards.iter().map(|card| card.1.shape).collect();
+        assert_eq!((colors.len(), shapes.len()), (4, 4));
+        assert_eq!(evil.cards, QuadDeck::<4>::from_seed(0).cards);
+    }
+
+    #[quickcheck]
+    fn find_quad_agrees_with_brute_force(seed: u64, size: u8) -> bool {
+        let deck = QuadDeck::<4>::standard_from_seed(seed);
+        let cards = &deck.cards[..(size % 16) as usize];
+
+        let brute_force = cards
+            .iter()
+            .tuple_combinations()
+            .any(|(a, b, c, d)| is_quad(a.0, b.0, c.0, d.0));
+
+        match find_quad(cards) {
+            Some([i, j, k, l]) => {
+                brute_force && is_quad(cards[i].0, cards[j].0, cards[k].0, cards[l].0)
+            }
+            None => !brute_force,
+        }
+    }
+
+    #[test]
+    fn quads_game_plays_to_the_end() {
+        let deck = QuadDeck::<4>::from_seed(3);
+        let mut game = GameDeck::start_quads_play(&deck).unwrap();
+
+        let mut quads_played = 0;
+        loop {
+            let quad = find_quad(game.in_play()).unwrap().to_vec();
+            quads_played += 1;
+            match game.play_selection(quad) {
+                Ok(PlayResponse::ValidPlay) => {}
+                Ok(PlayResponse::GameOver) => break,
+                other => panic!("unexpected response {:?}", other),
+            }
+        }
+
+        assert!(game.is_over());
+        assert!(game.in_deck().is_empty());
+        assert_eq!(game.in_play().len() + 4 * quads_played, 256);
+    }
+}
@@ -0,0 +1,50 @@
+/// Rules controlling how cards are dealt during a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameRules {
+    /// Number of cards dealt at the start of the game, and the size the board is kept at.
+    pub initial_board_size: usize,
+    /// Number of cards dealt at a time when more cards are needed.
+    pub refill_size: usize,
+    /// Largest number of cards that can be in play, or `None` for no limit.
+    pub max_board_size: Option<usize>,
+    /// Whether the engine keeps dealing cards until there is a set/ultraset in play. When this
+    /// is off, players have to ask for more cards with `GameDeck::deal_more`.
+    pub guarantee_move: bool,
+    /// Whether played cards are removed instead of replaced while there are more than
+    /// `initial_board_size` cards in play, shrinking the board back down.
+    pub collapse_extra_cards: bool,
+}
+
+impl GameRules {
+    /// The standard rules of Set: twelve cards, three more whenever there is no set.
+    #[must_use]
+    pub fn set() -> Self {
+        Self {
+            initial_board_size: 12,
+            refill_size: 3,
+            max_board_size: None,
+            guarantee_move: true,
+            collapse_extra_cards: true,
+        }
+    }
+
+    /// The standard rules of UltraSet: twelve cards, four more whenever there is no ultraset.
+    #[must_use]
+    pub fn ultraset() -> Self {
+        Self {
+            refill_size: 4,
+            ..Self::set()
+        }
+    }
+
+    /// Number of cards that can be dealt onto a board of `in_play` cards from a deck of
+    /// `in_deck` cards in one go.
+    pub(crate) fn deal_size(&self, in_play: usize, in_deck: usize) -> usize {
+        let room = match self.max_board_size {
+            Some(max_board_size) => max_board_size.saturating_sub(in_play),
+            None => usize::MAX,
+        };
+        self.refill_size.min(in_deck).min(room)
+    }
+}
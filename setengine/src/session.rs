@@ -0,0 +1,368 @@
+use crate::{GameDeck, GameVariant, PlayError, PlayResponse, SetVariant};
+use std::fmt;
+use std::time::Duration;
+
+/// How claims are scored in a multiplayer session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoringRules {
+    /// Points gained for a correct claim
+    pub points_per_move: i32,
+    /// Points lost for a wrong claim. Scores can go below zero.
+    pub wrong_claim_penalty: i32,
+    /// How long a player cannot claim after a wrong claim
+    pub lockout: Duration,
+}
+
+/// A player taking part in a session, and how they have done so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Player {
+    pub name: String,
+    pub score: i32,
+    pub correct_claims: usize,
+    pub wrong_claims: usize,
+    /// Time until which the player is locked out, if they have ever been
+    pub locked_until: Option<Duration>,
+}
+
+/// A claim that was judged, correct or not
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Claim {
+    /// Index of the claiming player in `Session::players`
+    pub player: usize,
+    /// Indices of the claimed cards in `in_play` when the claim was made
+    pub selection: Vec<usize>,
+    /// Time of the claim since the start of the session
+    pub at: Duration,
+    /// Whether the claim was a valid play
+    pub correct: bool,
+}
+
+/// The position of a player at the end of (or during) a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Standing {
+    /// 1 for the best score. Players with the same score share a rank.
+    pub rank: usize,
+    /// Index of the player in `Session::players`
+    pub player: usize,
+    pub score: i32,
+}
+
+/// Reasons a claim is refused without being judged. Neither the game nor the scores change when
+/// this is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SessionError {
+    /// There is no player with this index.
+    UnknownPlayer(usize),
+    /// The player made a wrong claim recently, and cannot claim until `until`.
+    LockedOut { player: usize, until: Duration },
+    /// The game is over, so there is nothing left to claim.
+    GameOver,
+    /// The selection is malformed, e.g. it has the wrong number of cards.
+    Play(PlayError),
+}
+
+/// A game played by several players, who claim moves against each other. The session judges
+/// every claim against its `GameDeck`, attributes it to the claiming player and applies the
+/// penalties of its `ScoringRules` to wrong claims.
+///
+/// Times are passed in by the caller as the time since the session started, so that the
+/// session works the same with any clock, and in replays.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "V: serde::Serialize, V::Card: serde::Serialize",
+        deserialize = "V: serde::Deserialize<'de>, V::Card: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Session<V: GameVariant = SetVariant> {
+    game: GameDeck<V>,
+    scoring: ScoringRules,
+    players: Vec<Player>,
+    claims: Vec<Claim>,
+}
+
+impl ScoringRules {
+    /// One point for a correct claim, one point lost and three seconds of lockout for a wrong
+    /// one.
+    #[must_use]
+    pub fn standard() -> Self {
+        Self {
+            points_per_move: 1,
+            wrong_claim_penalty: 1,
+            lockout: Duration::from_secs(3),
+        }
+    }
+}
+
+impl Player {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            score: 0,
+            correct_claims: 0,
+            wrong_claims: 0,
+            locked_until: None,
+        }
+    }
+
+    /// Checks if the player is locked out at time `at`
+    pub fn is_locked_out(&self, at: Duration) -> bool {
+        matches!(self.locked_until, Some(until) if at < until)
+    }
+}
+
+impl<V: GameVariant> Session<V> {
+    /// Starts a session on `game` with one player for each name. Players are referred to by
+    /// their index in `names`.
+    pub fn new<S: Into<String>>(
+        game: GameDeck<V>,
+        names: impl IntoIterator<Item = S>,
+        scoring: ScoringRules,
+    ) -> Self {
+        Self {
+            game,
+            scoring,
+            players: names
+                .into_iter()
+                .map(|name| Player::new(name.into()))
+                .collect(),
+            claims: Vec::new(),
+        }
+    }
+
+    /// Returns the game being played
+    pub fn game(&self) -> &GameDeck<V> {
+        &self.game
+    }
+
+    /// Returns the scoring rules of the session
+    pub fn scoring(&self) -> &ScoringRules {
+        &self.scoring
+    }
+
+    /// Returns the players, in the order they were given
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Returns every claim judged so far, oldest first
+    pub fn claims(&self) -> &[Claim] {
+        &self.claims
+    }
+
+    /// Checks if the session is over, which is when its game is over
+    pub fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    /// Judges a claim of `player` at time `at`. A correct claim is played and scored like
+    /// `GameDeck::play_selection`, and the response says if it ended the game. A wrong claim
+    /// returns `PlayResponse::InvalidPlay`, costs the player `wrong_claim_penalty` points and
+    /// locks them out for `lockout`.
+    pub fn claim(
+        &mut self,
+        player: usize,
+        selection: Vec<usize>,
+        at: Duration,
+    ) -> Result<PlayResponse, SessionError> {
+        let claimant = self
+            .players
+            .get(player)
+            .ok_or(SessionError::UnknownPlayer(player))?;
+        if claimant.is_locked_out(at) {
+            return Err(SessionError::LockedOut {
+                player,
+                until: claimant.locked_until.unwrap(),
+            });
+        }
+        if self.game.is_over() {
+            return Err(SessionError::GameOver);
+        }
+
+        let response = self
+            .game
+            .play_selection(selection.clone())
+            .map_err(SessionError::Play)?;
+
+        let claimant = &mut self.players[player];
+        let correct = response != PlayResponse::InvalidPlay;
+        if correct {
+            claimant.score += self.scoring.points_per_move;
+            claimant.correct_claims += 1;
+        } else {
+            claimant.score -= self.scoring.wrong_claim_penalty;
+            claimant.wrong_claims += 1;
+            claimant.locked_until = Some(at + self.scoring.lockout);
+        }
+
+        self.claims.push(Claim {
+            player,
+            selection,
+            at,
+            correct,
+        });
+        Ok(response)
+    }
+
+    /// Deals more cards, for games whose rules do not guarantee a move. See
+    /// `GameDeck::deal_more`.
+    pub fn deal_more(&mut self) -> Result<(), PlayError> {
+        self.game.deal_more()
+    }
+
+    /// Returns the players ordered from the highest score to the lowest. Players with the same
+    /// score share a rank, and are listed in the order they were given.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(player, p)| Standing {
+                rank: 0,
+                player,
+                score: p.score,
+            })
+            .collect();
+        standings.sort_by_key(|standing| std::cmp::Reverse(standing.score));
+
+        for i in 0..standings.len() {
+            standings[i].rank = if i > 0 && standings[i].score == standings[i - 1].score {
+                standings[i - 1].rank
+            } else {
+                i + 1
+            };
+        }
+        standings
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::UnknownPlayer(player) => write!(f, "there is no player {}", player),
+            SessionError::LockedOut { player, until } => write!(
+                f,
+                "player {} is locked out until {:.1}s",
+                player,
+                until.as_secs_f64()
+            ),
+            SessionError::GameOver => write!(f, "the game is over"),
+            SessionError::Play(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScoringRules, Session, SessionError, Standing};
+    use crate::{Deck, GameDeck, PlayResponse};
+    use std::time::Duration;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn claims_are_scored_and_penalized() {
+        let game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(5)).unwrap();
+        let mut session = Session::new(game, ["Ada", "Bo", "Cy"], ScoringRules::standard());
+
+        let set = session.game().all_moves().remove(0);
+        let not_set = (0..session.game().in_play().len())
+            .map(|i| vec![set[0], set[1], i])
+            .find(|selection| {
+                let cards: Vec<_> = selection
+                    .iter()
+                    .map(|&i| session.game().in_play()[i])
+                    .collect();
+                selection[2] != set[0] && selection[2] != set[1] && !crate::selection_is_set(&cards)
+            })
+            .unwrap();
+
+        assert_eq!(
+            session.claim(1, not_set.clone(), secs(1)),
+            Ok(PlayResponse::InvalidPlay)
+        );
+        assert_eq!(
+            session.claim(1, set.clone(), secs(2)),
+            Err(SessionError::LockedOut {
+                player: 1,
+                until: secs(4)
+            })
+        );
+        assert_eq!(
+            session.claim(3, set.clone(), secs(2)),
+            Err(SessionError::UnknownPlayer(3))
+        );
+        assert!(matches!(
+            session.claim(0, vec![0, 1], secs(2)),
+            Err(SessionError::Play(_))
+        ));
+        assert_eq!(session.claim(0, set, secs(2)), Ok(PlayResponse::ValidPlay));
+
+        let set = session.game().all_moves().remove(0);
+        assert_eq!(session.claim(1, set, secs(4)), Ok(PlayResponse::ValidPlay));
+
+        let players = session.players();
+        assert_eq!(
+            (players[0].score, players[1].score, players[2].score),
+            (1, 0, 0)
+        );
+        assert_eq!((players[1].correct_claims, players[1].wrong_claims), (1, 1));
+        assert_eq!(session.claims().len(), 3);
+        assert!(!session.claims()[0].correct);
+
+        assert_eq!(
+            session.standings(),
+            vec![
+                Standing {
+                    rank: 1,
+                    player: 0,
+                    score: 1
+                },
+                Standing {
+                    rank: 2,
+                    player: 1,
+                    score: 0
+                },
+                Standing {
+                    rank: 2,
+                    player: 2,
+                    score: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sessions_end_with_the_game() {
+        let game = GameDeck::start_ultraset_play(&Deck::<4>::standard_from_seed(6)).unwrap();
+        let mut session = Session::new(game, ["Ada", "Bo"], ScoringRules::standard());
+
+        let mut turn = 0;
+        while let Some(selection) = session.game().all_moves().pop() {
+            session
+                .claim(turn % 2, selection, secs(turn as u64))
+                .unwrap();
+            turn += 1;
+        }
+        assert!(session.is_over());
+        assert_eq!(
+            session.claim(0, vec![0, 1, 2, 3], secs(100)),
+            Err(SessionError::GameOver)
+        );
+
+        let total: i32 = session.players().iter().map(|player| player.score).sum();
+        assert_eq!(total as usize, turn);
+        assert_eq!(session.standings()[0].rank, 1);
+    }
+}
@@ -0,0 +1,187 @@
+//! Monte Carlo simulation of whole games of Set and UltraSet, for statistics such as how often
+//! a board has no set, how large boards get, and how many cards are left at the end.
+
+use crate::{Deck, GameDeck, GameRules, GameVariant, PlayResponse, SetVariant};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::collections::BTreeMap;
+
+/// How a simulated player picks a move when there are several
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// The first set or ultraset found, in the order `find_all_sets`/`find_all_ultrasets` list
+    /// them
+    FirstMove,
+    /// A set or ultraset picked uniformly at random
+    RandomMove,
+}
+
+/// What happened in one simulated game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameSummary {
+    /// Number of sets or ultrasets played
+    pub plays: usize,
+    /// Size of every board the player looked for a move on, and whether it had one
+    pub boards: Vec<(usize, bool)>,
+    /// Largest number of cards in play at any point
+    pub largest_board: usize,
+    /// Number of cards in play when the game ended
+    pub cards_left: usize,
+}
+
+/// How many boards of a given size were seen, and how many of them had no move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BoardCount {
+    pub seen: usize,
+    pub without_move: usize,
+}
+
+/// Statistics aggregated over many simulated games
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// Number of games played
+    pub games: usize,
+    /// Boards seen, by number of cards on the board
+    pub boards: BTreeMap<usize, BoardCount>,
+    /// Number of games by the largest board reached
+    pub largest_board: BTreeMap<usize, usize>,
+    /// Number of games by the number of cards left at the end
+    pub cards_left: BTreeMap<usize, usize>,
+}
+
+impl BoardCount {
+    /// Fraction of the boards seen that had no move
+    pub fn probability_without_move(&self) -> f64 {
+        self.without_move as f64 / self.seen as f64
+    }
+}
+
+impl Statistics {
+    /// Adds the outcome of one game
+    pub fn add(&mut self, game: &GameSummary) {
+        self.games += 1;
+        for &(size, has_move) in &game.boards {
+            let count = self.boards.entry(size).or_default();
+            count.seen += 1;
+            if !has_move {
+                count.without_move += 1;
+            }
+        }
+        *self.largest_board.entry(game.largest_board).or_default() += 1;
+        *self.cards_left.entry(game.cards_left).or_default() += 1;
+    }
+}
+
+/// Plays `games` games of `variant` with the standard rules and `policy`, and aggregates
+/// their statistics. The decks and the random moves are drawn from `seed`, so the same seed
+/// always gives the same statistics.
+pub fn simulate<const N: usize>(
+    variant: SetVariant<N>,
+    policy: Policy,
+    games: usize,
+    seed: u64,
+) -> Statistics {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut statistics = Statistics::default();
+    for _ in 0..games {
+        let deck = Deck::<N>::new_standard_deck_with_rng(&mut rng);
+        statistics.add(&simulate_game(variant, &deck, policy, &mut rng));
+    }
+    statistics
+}
+
+/// Plays one game of `variant` with `deck` and the standard rules, picking moves with
+/// `policy`. The player asks for more cards whenever there is no move, so every board without
+/// a move is seen.
+pub fn simulate_game<const N: usize, R: Rng + ?Sized>(
+    variant: SetVariant<N>,
+    deck: &Deck<N>,
+    policy: Policy,
+    rng: &mut R,
+) -> GameSummary {
+    let rules = GameRules {
+        guarantee_move: false,
+        ..variant.default_rules()
+    };
+    let mut game = GameDeck::start_with_rules(variant, &deck.cards, rules)
+        .expect("games that do not guarantee a move always start");
+
+    let mut summary = GameSummary {
+        plays: 0,
+        boards: Vec::new(),
+        largest_board: game.in_play().len(),
+        cards_left: 0,
+    };
+
+    loop {
+        let moves = game.all_moves();
+        summary
+            .boards
+            .push((game.in_play().len(), !moves.is_empty()));
+
+        let selection = match policy {
+            Policy::FirstMove => moves.first(),
+            Policy::RandomMove => moves.choose(rng),
+        };
+        let response = match selection {
+            Some(selection) => {
+                summary.plays += 1;
+                game.play_selection(selection.clone())
+                    .expect("moves found on the board are well formed")
+            }
+            None => match game.deal_more() {
+                Ok(()) => PlayResponse::ValidPlay,
+                Err(_) => PlayResponse::GameOver,
+            },
+        };
+
+        summary.largest_board = summary.largest_board.max(game.in_play().len());
+        if response == PlayResponse::GameOver {
+            break;
+        }
+    }
+
+    summary.cards_left = game.in_play().len();
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{simulate, simulate_game, Policy};
+    use crate::{Deck, SetVariant};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn simulated_games_account_for_every_card() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for policy in [Policy::FirstMove, Policy::RandomMove] {
+            let deck = Deck::<4>::standard_from_seed(1);
+            let summary = simulate_game(SetVariant::Set, &deck, policy, &mut rng);
+            assert_eq!(3 * summary.plays + summary.cards_left, 81);
+
+            let deck = Deck::<4>::standard_from_seed(1);
+            let summary = simulate_game(SetVariant::UltraSet, &deck, policy, &mut rng);
+            assert_eq!(4 * summary.plays + summary.cards_left, 81);
+        }
+    }
+
+    #[test]
+    fn simulations_are_reproducible() {
+        let statistics = simulate(SetVariant::<4>::Set, Policy::RandomMove, 50, 3);
+        assert_eq!(
+            statistics,
+            simulate(SetVariant::<4>::Set, Policy::RandomMove, 50, 3)
+        );
+        assert_eq!(statistics.games, 50);
+        assert_eq!(statistics.largest_board.values().sum::<usize>(), 50);
+        assert_eq!(statistics.cards_left.values().sum::<usize>(), 50);
+
+        // Every board of 21 cards has a set, so the board never grows past that
+        assert!(statistics.boards.keys().all(|&size| size <= 21));
+        assert!(statistics
+            .boards
+            .range(21..)
+            .all(|(_, count)| count.without_move == 0));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{CardCoordinates, GameDeck, SetVariant};
+use std::collections::HashMap;
+
+/// The best line of play found from a position of a game
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solution {
+    /// The moves to make, each in the form of `Move::selection`: the indices of the cards to
+    /// play in `in_play` at that point, in increasing order, or nothing to deal more cards.
+    pub line: Vec<Vec<usize>>,
+    /// Number of cards in play or in the deck at the end of the line
+    pub cards_left: usize,
+    /// Whether no line leaves fewer cards. This is false when the search gave up before
+    /// looking at every line.
+    pub optimal: bool,
+}
+
+/// A position from the point of view of what can still happen: the cards in play, in any
+/// order, and how many are left in the deck. The deck is always dealt in the same order.
+type Position = (Vec<usize>, usize);
+
+/// What is known about a position that has been searched: the fewest cards it can be
+/// played down to, and the cards of the first move that does it. Dealing is written as no
+/// cards, and the end of the game as `None`.
+type Outcome<const N: usize> = (usize, Option<Vec<CardCoordinates<N>>>);
+
+struct Search<const N: usize> {
+    outcomes: HashMap<Position, Outcome<N>>,
+    max_positions: usize,
+    gave_up: bool,
+}
+
+impl<const N: usize> GameDeck<SetVariant<N>> {
+    /// Searches for the line of play from the current position that leaves the fewest cards
+    /// at the end of the game, stopping early if it clears every card. To analyse a whole
+    /// game, call this on a game that was just started, or undo every move first.
+    ///
+    /// More cards are only dealt when there is no move in play. At most `max_positions`
+    /// positions are searched, after which the search plays the first move it finds until the
+    /// game is over, so that it still returns a line of play.
+    pub fn solve(&self, max_positions: usize) -> Solution {
+        let mut search = Search {
+            outcomes: HashMap::new(),
+            max_positions,
+            gave_up: false,
+        };
+        let mut game = self.clone();
+        let cards_left = search.search(&mut game);
+
+        // Following the best first moves of every position from the start
+        let mut line = Vec::new();
+        let mut game = self.clone();
+        while let Some((_, Some(cards))) = search.outcomes.get(&position(&game)) {
+            let mut selection: Vec<usize> = cards
+                .iter()
+                .filter_map(|&card| game.in_play().iter().position(|in_play| in_play.0 == card))
+                .collect();
+            selection.sort_unstable();
+
+            let made = if selection.is_empty() {
+                game.deal_more().is_ok()
+            } else {
+                game.play_selection(selection.clone()).is_ok()
+            };
+            if !made {
+                break;
+            }
+            line.push(selection);
+        }
+
+        Solution {
+            line,
+            cards_left,
+            optimal: !search.gave_up || cards_left == 0,
+        }
+    }
+}
+
+impl<const N: usize> Search<N> {
+    /// Returns the fewest cards `game` can be played down to, leaving it as it was
+    fn search(&mut self, game: &mut GameDeck<SetVariant<N>>) -> usize {
+        let key = position(game);
+        if let Some(&(cards_left, _)) = self.outcomes.get(&key) {
+            return cards_left;
+        }
+        if self.outcomes.len() >= self.max_positions {
+            self.gave_up = true;
+        }
+
+        let mut moves = game.all_moves();
+        if moves.is_empty() {
+            moves.push(Vec::new());
+        }
+
+        let mut best: Outcome<N> = (game.in_play().len() + game.in_deck().len(), None);
+        for selection in moves {
+            let cards: Vec<CardCoordinates<N>> = selection
+                .iter()
+                .map(|&index| game.in_play()[index].0)
+                .collect();
+            let made = if selection.is_empty() {
+                game.deal_more().is_ok()
+            } else {
+                game.play_selection(selection).is_ok()
+            };
+            if !made {
+                continue;
+            }
+
+            let cards_left = self.search(game);
+            game.undo().expect("the move was just made");
+            if best.1.is_none() || cards_left < best.0 {
+                best = (cards_left, Some(cards));
+            }
+            if cards_left == 0 || self.gave_up {
+                break;
+            }
+        }
+
+        let cards_left = best.0;
+        self.outcomes.insert(key, best);
+        cards_left
+    }
+}
+
+fn position<const N: usize>(game: &GameDeck<SetVariant<N>>) -> Position {
+    let mut in_play: Vec<usize> = game.in_play().iter().map(|card| card.0.index()).collect();
+    in_play.sort_unstable();
+    (in_play, game.in_deck().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Deck, GameDeck, GameRules, SetVariant};
+
+    /// Tries every line of play without remembering positions, for comparison
+    fn fewest_cards_left(game: &mut GameDeck<SetVariant<3>>) -> usize {
+        let mut moves = game.all_moves();
+        if moves.is_empty() {
+            moves.push(Vec::new());
+        }
+
+        let mut fewest = game.in_play().len() + game.in_deck().len();
+        for selection in moves {
+            let made = if selection.is_empty() {
+                game.deal_more().is_ok()
+            } else {
+                game.play_selection(selection).is_ok()
+            };
+            if made {
+                fewest = fewest.min(fewest_cards_left(game));
+                game.undo().unwrap();
+            }
+        }
+        fewest
+    }
+
+    #[quickcheck]
+    fn solutions_are_the_best_lines_of_play(seed: u64, ultraset: bool) -> bool {
+        let variant = if ultraset {
+            SetVariant::UltraSet
+        } else {
+            SetVariant::Set
+        };
+        let rules = GameRules {
+            initial_board_size: 6,
+            ..GameRules::set()
+        };
+        let deck = Deck::<3>::standard_from_seed(seed);
+        let game = match GameDeck::start_with_rules(variant, &deck.cards, rules) {
+            Ok(game) => game,
+            Err(_) => return true,
+        };
+
+        let solution = game.solve(usize::MAX);
+        let mut replayed = game.clone();
+        let line_is_playable = solution.line.iter().all(|selection| {
+            if selection.is_empty() {
+                replayed.deal_more().is_ok()
+            } else {
+                replayed.play_selection(selection.clone()).is_ok()
+            }
+        });
+
+        solution.optimal
+            && line_is_playable
+            && replayed.is_over()
+            && replayed.in_play().len() + replayed.in_deck().len() == solution.cards_left
+            && solution.cards_left == fewest_cards_left(&mut game.clone())
+    }
+
+    #[test]
+    fn searches_that_give_up_still_finish_the_game() {
+        let game = GameDeck::start_set_play(&Deck::<4>::standard_from_seed(5)).unwrap();
+        let solution = game.solve(10);
+
+        let mut replayed = game.clone();
+        for selection in &solution.line {
+            replayed.play_selection(selection.clone()).unwrap();
+        }
+        assert!(replayed.is_over());
+        assert_eq!(replayed.in_play().len(), solution.cards_left);
+        assert_eq!(solution.optimal, solution.cards_left == 0);
+    }
+}
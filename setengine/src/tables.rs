@@ -0,0 +1,56 @@
+//! Lookup tables for arithmetic in F_3^N, with cards encoded as base 3 indices.
+//!
+//! The tables are built for the largest supported number of attributes. A card with fewer
+//! attributes has zeros in its leading digits, and adding such cards never makes those digits
+//! non-zero, so the same tables serve every deck size.
+
+/// Largest number of attributes a card can have.
+pub(crate) const MAX_ATTRIBUTES: usize = 5;
+/// Number of cards in F_3^MAX_ATTRIBUTES.
+pub(crate) const MAX_CARDS: usize = 243;
+
+/// `ADDITION[a][b]` is the index of the sum of cards `a` and `b`.
+pub(crate) static ADDITION: [[u8; MAX_CARDS]; MAX_CARDS] = build_table(false);
+/// `COMPLETION[a][b]` is the index of the card completing `a` and `b` to a set.
+pub(crate) static COMPLETION: [[u8; MAX_CARDS]; MAX_CARDS] = build_table(true);
+/// `NEGATION[a]` is the index of the additive inverse of card `a`.
+pub(crate) static NEGATION: [u8; MAX_CARDS] = build_negation();
+
+/// Adds `a` and `b` digitwise, or completes them to a set if `complete` is true.
+const fn combine(a: usize, b: usize, complete: bool) -> u8 {
+    let mut result = 0;
+    let mut place = 1;
+    let mut i = 0;
+    while i < MAX_ATTRIBUTES {
+        let sum = (a / place) % 3 + (b / place) % 3;
+        let digit = if complete { (6 - sum) % 3 } else { sum % 3 };
+        result += digit * place;
+        place *= 3;
+        i += 1;
+    }
+    result as u8
+}
+
+const fn build_table(complete: bool) -> [[u8; MAX_CARDS]; MAX_CARDS] {
+    let mut table = [[0; MAX_CARDS]; MAX_CARDS];
+    let mut a = 0;
+    while a < MAX_CARDS {
+        let mut b = 0;
+        while b < MAX_CARDS {
+            table[a][b] = combine(a, b, complete);
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+}
+
+const fn build_negation() -> [u8; MAX_CARDS] {
+    let mut table = [0; MAX_CARDS];
+    let mut a = 0;
+    while a < MAX_CARDS {
+        table[a] = combine(0, a, true);
+        a += 1;
+    }
+    table
+}
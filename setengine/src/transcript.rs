@@ -0,0 +1,649 @@
+use crate::deck::visual_attributes;
+use crate::{
+    format_board, parse_board, CardCoordinates, Deck, GameDeck, GameRules, GameVariant, PlayError,
+    PlayResponse, SetVariant,
+};
+use cardgen::{generate_standard_attributes, Attributes, CardVisualAttr};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// One entry of a transcript: the cards played, and when
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedPlay<const N: usize = 4> {
+    /// Time since the start of the game, which transcripts keep to the millisecond
+    pub time: Duration,
+    /// The cards played. Empty if more cards were dealt instead, like `Move::selection`.
+    pub cards: Vec<CardCoordinates<N>>,
+}
+
+/// A record of a game from which it can be replayed: the variant, rules and cards it was
+/// dealt with, and every play made. See the crate documentation for its text format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transcript<const N: usize = 4> {
+    pub variant: SetVariant<N>,
+    pub rules: GameRules,
+    /// What each value of each attribute is drawn as, which is random in Evil Set
+    pub attributes: Attributes,
+    /// The cards before dealing, in the order of `Deck::cards`
+    pub deck: Vec<CardCoordinates<N>>,
+    pub plays: Vec<TimedPlay<N>>,
+    /// Any other tags, such as the names of the players, in the order they are written
+    tags: Vec<(String, String)>,
+}
+
+/// The tags every transcript is written with, in order
+const STANDARD_TAGS: [&str; 11] = [
+    "Variant",
+    "BoardSize",
+    "RefillSize",
+    "MaxBoardSize",
+    "GuaranteeMove",
+    "CollapseExtraCards",
+    "Numbers",
+    "Colors",
+    "Shapes",
+    "Fillings",
+    "Deck",
+];
+
+/// The error returned when a transcript cannot be read or replayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptError {
+    /// A line could not be read. Lines are counted from 1.
+    Syntax { line: usize, message: String },
+    /// A tag the transcript cannot do without is missing.
+    MissingTag(&'static str),
+    /// The cards cannot be dealt with the rules of the transcript.
+    Start(PlayError),
+    /// The play with this number, counted from 1, cannot be made at that point of the game.
+    IllegalPlay(usize),
+    /// A tag cannot have this name: it is empty, has whitespace or `]` in it, or is the name of
+    /// a standard tag.
+    TagName(String),
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            TranscriptError::MissingTag(tag) => write!(f, "the transcript has no {} tag", tag),
+            TranscriptError::Start(error) => write!(f, "the game cannot be dealt: {}", error),
+            TranscriptError::IllegalPlay(number) => {
+                write!(f, "play {} cannot be made in the game", number)
+            }
+            TranscriptError::TagName(name) => write!(f, "\"{}\" cannot be a tag name", name),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptError {}
+
+impl<const N: usize> Transcript<N> {
+    /// Starts the transcript of a game of `variant` dealt from `deck` with `rules`, before
+    /// any play has been made.
+    pub fn new(variant: SetVariant<N>, rules: GameRules, deck: &Deck<N>) -> Self {
+        Self {
+            variant,
+            rules,
+            attributes: deck.attributes(),
+            deck: deck.cards.iter().map(|card| card.0).collect(),
+            plays: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Brings the transcript up to date with the history of `game`, a game started like the
+    /// transcript: plays that are no longer in the history, because they were undone, are
+    /// taken off, and the moves made since are added as made `time` after the start. Call this
+    /// after every play, deal and undo. Does nothing if the history has not changed, such as
+    /// after an invalid play.
+    pub fn record_new_moves(&mut self, game: &GameDeck<SetVariant<N>>, time: Duration) {
+        let kept = self
+            .plays
+            .iter()
+            .zip(game.history())
+            .take_while(|(play, record)| {
+                play.cards
+                    .iter()
+                    .eq(record.removed.iter().map(|card| &card.0))
+            })
+            .count();
+        self.plays.truncate(kept);
+
+        let new_plays = game.history()[kept..].iter().map(|record| TimedPlay {
+            time,
+            cards: record.removed.iter().map(|card| card.0).collect(),
+        });
+        self.plays.extend(new_plays);
+    }
+
+    /// Returns the tags other than the standard ones, in the order they are written
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// Adds a tag to be written after the standard ones, such as the name of a player. Fails
+    /// if the name is empty, has whitespace or `]` in it, or is that of a standard tag.
+    pub fn add_tag(&mut self, name: &str, value: &str) -> Result<(), TranscriptError> {
+        if !is_tag_name(name) || STANDARD_TAGS.contains(&name) {
+            return Err(TranscriptError::TagName(name.to_string()));
+        }
+        self.tags.push((name.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    /// Returns the cards of the deck with what is drawn on them
+    pub fn cards(&self) -> Vec<(CardCoordinates<N>, CardVisualAttr)> {
+        self.deck
+            .iter()
+            .map(|&coordinates| {
+                (
+                    coordinates,
+                    visual_attributes(coordinates, &self.attributes),
+                )
+            })
+            .collect()
+    }
+
+    /// Rebuilds the game as it was after the first `plays` plays, or after every play if there
+    /// are fewer. The replayed plays are in the history of the game, so they can be undone.
+    pub fn replay(&self, plays: usize) -> Result<GameDeck<SetVariant<N>>, TranscriptError> {
+        let mut game = GameDeck::start_with_rules(self.variant, &self.cards(), self.rules)
+            .map_err(TranscriptError::Start)?;
+
+        for (number, play) in (1..).zip(self.plays.iter().take(plays)) {
+            if play.cards.is_empty() {
+                game.deal_more()
+                    .map_err(|_| TranscriptError::IllegalPlay(number))?;
+                continue;
+            }
+
+            let selection = play
+                .cards
+                .iter()
+                .map(|&card| game.in_play().iter().position(|in_play| in_play.0 == card))
+                .collect::<Option<Vec<usize>>>()
+                .ok_or(TranscriptError::IllegalPlay(number))?;
+            match game.play_selection(selection) {
+                Ok(PlayResponse::ValidPlay | PlayResponse::GameOver) => {}
+                _ => return Err(TranscriptError::IllegalPlay(number)),
+            }
+        }
+        Ok(game)
+    }
+
+    /// Rebuilds the game as it was after the last play
+    pub fn final_game(&self) -> Result<GameDeck<SetVariant<N>>, TranscriptError> {
+        self.replay(self.plays.len())
+    }
+}
+
+fn is_tag_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == ']')
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{}.{:03}", time.as_secs(), time.subsec_millis())
+}
+
+fn parse_time(time: &str) -> Result<Duration, String> {
+    let error = || format!("\"{}\" is not a time in seconds", time);
+    let (secs, fraction) = time.split_once('.').unwrap_or((time, ""));
+    if secs.is_empty() || !time.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return Err(error());
+    }
+
+    let secs: u64 = secs.parse().map_err(|_| error())?;
+    let millis = fraction
+        .chars()
+        .chain(std::iter::repeat('0'))
+        .take(3)
+        .collect::<String>()
+        .parse::<u64>()
+        .map_err(|_| error())?;
+    Ok(Duration::from_secs(secs) + Duration::from_millis(millis))
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+fn parse_yes_no(value: &str) -> Result<bool, String> {
+    match value {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(format!("expected yes or no, found \"{}\"", value)),
+    }
+}
+
+fn parse_size(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("\"{}\" is not a number of cards", value))
+}
+
+fn words<T: fmt::Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_values<T>(value: &str) -> Result<[T; 3], String>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let values = value
+        .split_whitespace()
+        .map(|word| word.parse().map_err(|error: T::Err| error.to_string()))
+        .collect::<Result<Vec<T>, String>>()?;
+    let found = values.len();
+    values
+        .try_into()
+        .map_err(|_| format!("expected 3 values, found {}", found))
+}
+
+/// Reads a line like `[Name "value"]`, where the value may contain `\"`, `\\`, `\n` and `\r`
+fn parse_tag(line: &str) -> Result<(String, String), String> {
+    let error = || format!("expected a tag like [Name \"value\"], found {}", line);
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+        .ok_or_else(error)?;
+    let (name, quoted) = inner.split_once(char::is_whitespace).ok_or_else(error)?;
+    if !is_tag_name(name) {
+        return Err(error());
+    }
+    let quoted = quoted
+        .trim()
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .ok_or_else(error)?;
+
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next().ok_or_else(error)? {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                escaped => value.push(escaped),
+            },
+            '"' => return Err(error()),
+            _ => value.push(c),
+        }
+    }
+    Ok((name.to_string(), value))
+}
+
+/// Reads a line like `3. 12.500 0000 1111 2222` or `4. 20.000 deal`
+fn parse_play<const N: usize>(line: &str, number: usize) -> Result<TimedPlay<N>, String> {
+    let mut words = line.split_whitespace();
+    let found = words.next().unwrap_or_default();
+    if found != format!("{}.", number) {
+        return Err(format!("expected play {}, found \"{}\"", number, found));
+    }
+
+    let time = parse_time(words.next().unwrap_or_default())?;
+    let rest: Vec<&str> = words.collect();
+    let cards = match rest[..] {
+        [] => return Err(format!("play {} has no cards", number)),
+        ["deal"] => Vec::new(),
+        _ => parse_board(&rest.join(" ")).map_err(|error| error.to_string())?,
+    };
+    Ok(TimedPlay { time, cards })
+}
+
+/// Written as tags, one per line, followed by a blank line and a line per play
+impl<const N: usize> fmt::Display for Transcript<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let variant = match self.variant {
+            SetVariant::Set => "Set",
+            SetVariant::UltraSet => "UltraSet",
+        };
+        let max_board_size = self
+            .rules
+            .max_board_size
+            .map_or_else(|| "-".to_string(), |size| size.to_string());
+
+        let tags = [
+            ("Variant", variant.to_string()),
+            ("BoardSize", self.rules.initial_board_size.to_string()),
+            ("RefillSize", self.rules.refill_size.to_string()),
+            ("MaxBoardSize", max_board_size),
+            ("GuaranteeMove", yes_no(self.rules.guarantee_move)),
+            (
+                "CollapseExtraCards",
+                yes_no(self.rules.collapse_extra_cards),
+            ),
+            ("Numbers", words(&self.attributes.numbers)),
+            ("Colors", words(&self.attributes.colors)),
+            ("Shapes", words(&self.attributes.shapes)),
+            ("Fillings", words(&self.attributes.fillings)),
+            ("Deck", format_board(&self.deck)),
+        ];
+        let extra_tags = self.tags.iter().map(|(name, value)| (name.as_str(), value));
+        for (name, value) in tags
+            .iter()
+            .map(|(name, value)| (*name, value))
+            .chain(extra_tags)
+        {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r");
+            writeln!(f, "[{} \"{}\"]", name, escaped)?;
+        }
+
+        writeln!(f)?;
+        for (number, play) in (1..).zip(&self.plays) {
+            let cards = if play.cards.is_empty() {
+                "deal".to_string()
+            } else {
+                format_board(&play.cards)
+            };
+            writeln!(f, "{}. {} {}", number, format_time(play.time), cards)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the format of `Display`. Blank lines and lines starting with `;` are skipped. Only
+/// the `Variant` and `Deck` tags are needed: the rules default to those of the variant, and
+/// the attributes to the standard ones.
+impl<const N: usize> FromStr for Transcript<N> {
+    type Err = TranscriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = Vec::new();
+        let mut plays = Vec::new();
+        for (line, text) in (1..).zip(s.lines()) {
+            let syntax = |message| TranscriptError::Syntax { line, message };
+            let text = text.trim();
+            if text.is_empty() || text.starts_with(';') {
+                continue;
+            } else if text.starts_with('[') {
+                let (name, value) = parse_tag(text).map_err(syntax)?;
+                tags.push((line, name, value));
+            } else {
+                plays.push(parse_play(text, plays.len() + 1).map_err(syntax)?);
+            }
+        }
+
+        let variant = read_tag(&mut tags, "Variant", |value| match value {
+            "Set" => Ok(SetVariant::Set),
+            "UltraSet" => Ok(SetVariant::UltraSet),
+            _ => Err(format!("\"{}\" is not a variant", value)),
+        })?
+        .ok_or(TranscriptError::MissingTag("Variant"))?;
+        let deck = read_tag(&mut tags, "Deck", |value| {
+            parse_board(value).map_err(|error| error.to_string())
+        })?
+        .ok_or(TranscriptError::MissingTag("Deck"))?;
+
+        let mut rules = variant.default_rules();
+        if let Some(size) = read_tag(&mut tags, "BoardSize", parse_size)? {
+            rules.initial_board_size = size;
+        }
+        if let Some(size) = read_tag(&mut tags, "RefillSize", parse_size)? {
+            rules.refill_size = size;
+        }
+        if let Some(size) = read_tag(&mut tags, "MaxBoardSize", |value| match value {
+            "-" => Ok(None),
+            _ => parse_size(value).map(Some),
+        })? {
+            rules.max_board_size = size;
+        }
+        if let Some(guarantee) = read_tag(&mut tags, "GuaranteeMove", parse_yes_no)? {
+            rules.guarantee_move = guarantee;
+        }
+        if let Some(collapse) = read_tag(&mut tags, "CollapseExtraCards", parse_yes_no)? {
+            rules.collapse_extra_cards = collapse;
+        }
+
+        let mut attributes = generate_standard_attributes();
+        if let Some(numbers) = read_tag(&mut tags, "Numbers", parse_values)? {
+            attributes.numbers = numbers;
+        }
+        if let Some(colors) = read_tag(&mut tags, "Colors", parse_values)? {
+            attributes.colors = colors;
+        }
+        if let Some(shapes) = read_tag(&mut tags, "Shapes", parse_values)? {
+            attributes.shapes = shapes;
+        }
+        if let Some(fillings) = read_tag(&mut tags, "Fillings", parse_values)? {
+            attributes.fillings = fillings;
+        }
+
+        if let Some((line, name, _)) = tags
+            .iter()
+            .find(|(_, name, _)| STANDARD_TAGS.contains(&name.as_str()))
+        {
+            let message = format!("the {} tag is given more than once", name);
+            return Err(TranscriptError::Syntax {
+                line: *line,
+                message,
+            });
+        }
+
+        Ok(Self {
+            variant,
+            rules,
+            attributes,
+            deck,
+            plays,
+            tags: tags
+                .into_iter()
+                .map(|(_, name, value)| (name, value))
+                .collect(),
+        })
+    }
+}
+
+/// Takes the first tag called `name` out of `tags` and reads its value with `parse`
+fn read_tag<T>(
+    tags: &mut Vec<(usize, String, String)>,
+    name: &str,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<Option<T>, TranscriptError> {
+    let position = match tags.iter().position(|(_, tag, _)| tag == name) {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+    let (line, _, value) = tags.remove(position);
+    parse(&value)
+        .map(Some)
+        .map_err(|message| TranscriptError::Syntax { line, message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Transcript, TranscriptError};
+    use crate::{Deck, GameDeck, GameRules, GameVariant, PlayResponse, SetVariant};
+    use itertools::Itertools;
+    use std::time::Duration;
+
+    /// Plays the first move found until the game is over, asking for more cards when there is
+    /// none, and returns the transcript with the board after every play.
+    fn record_game(seed: u64, ultraset: bool) -> (Transcript, Vec<Vec<String>>) {
+        let deck = Deck::<4>::from_seed(seed);
+        let variant = if ultraset {
+            SetVariant::UltraSet
+        } else {
+            SetVariant::Set
+        };
+        let rules = GameRules {
+            guarantee_move: false,
+            ..variant.default_rules()
+        };
+
+        let mut game = GameDeck::start_with_rules(variant, &deck.cards, rules).unwrap();
+        let mut transcript = Transcript::new(variant, rules, &deck);
+        let board = |game: &GameDeck| {
+            let cards = game.in_play().iter();
+            cards
+                .map(|(card, visual)| format!("{} {}", card, visual))
+                .collect()
+        };
+        let mut boards = vec![board(&game)];
+        for play in 1..=40 {
+            let moved = match variant.find_move(game.in_play()) {
+                Some(selection) => game.play_selection(selection).is_ok(),
+                None => game.deal_more().is_ok(),
+            };
+            if !moved {
+                break;
+            }
+            transcript.record_new_moves(&game, Duration::from_millis(1234 * play as u64));
+            boards.push(board(&game));
+        }
+        (transcript, boards)
+    }
+
+    #[quickcheck]
+    fn transcripts_replay_the_recorded_game(seed: u64, ultraset: bool) -> bool {
+        let (transcript, boards) = record_game(seed, ultraset);
+        let read_back = match transcript.to_string().parse::<Transcript>() {
+            Ok(read_back) => read_back,
+            Err(_) => return false,
+        };
+
+        read_back == transcript
+            && boards.iter().enumerate().all(|(plays, board)| {
+                let game = read_back.replay(plays).unwrap();
+                let replayed: Vec<String> = game
+                    .in_play()
+                    .iter()
+                    .map(|(card, visual)| format!("{} {}", card, visual))
+                    .collect();
+                game.history().len() == plays && &replayed == board
+            })
+    }
+
+    #[test]
+    fn invalid_plays_are_not_recorded() {
+        let deck = Deck::<4>::standard_from_seed(2);
+        let mut game = GameDeck::start_set_play(&deck).unwrap();
+        let mut transcript = Transcript::new(SetVariant::Set, *game.rules(), &deck);
+
+        let selection = game.variant().find_move(game.in_play()).unwrap();
+        game.play_selection(selection).unwrap();
+        transcript.record_new_moves(&game, Duration::from_secs(1));
+
+        let not_a_set = (0..game.in_play().len())
+            .tuple_combinations()
+            .map(|(a, b, c)| vec![a, b, c])
+            .find(|selection| {
+                let cards: Vec<_> = selection.iter().map(|&i| game.in_play()[i]).collect();
+                !game.variant().is_valid(&cards)
+            })
+            .unwrap();
+        assert_eq!(
+            game.play_selection(not_a_set),
+            Ok(PlayResponse::InvalidPlay)
+        );
+        transcript.record_new_moves(&game, Duration::from_secs(2));
+
+        assert_eq!(transcript.plays.len(), 1);
+        let replayed = transcript.final_game().unwrap();
+        assert_eq!(replayed.in_play(), game.in_play());
+    }
+
+    #[test]
+    fn undone_moves_are_taken_off() {
+        let (deck, mut game) = (0..)
+            .map(|seed| {
+                let deck = Deck::<4>::standard_from_seed(seed);
+                let game = GameDeck::start_set_play(&deck).unwrap();
+                (deck, game)
+            })
+            .find(|(_, game)| game.all_moves().len() >= 2)
+            .unwrap();
+        let mut transcript = Transcript::new(SetVariant::Set, *game.rules(), &deck);
+        let moves = game.all_moves();
+
+        game.play_selection(moves[0].clone()).unwrap();
+        transcript.record_new_moves(&game, Duration::from_secs(1));
+        game.undo().unwrap();
+        transcript.record_new_moves(&game, Duration::from_secs(2));
+        assert!(transcript.plays.is_empty());
+
+        // Undoing and playing another move before the transcript hears of either
+        game.play_selection(moves[0].clone()).unwrap();
+        transcript.record_new_moves(&game, Duration::from_secs(3));
+        game.undo().unwrap();
+        game.play_selection(moves[1].clone()).unwrap();
+        transcript.record_new_moves(&game, Duration::from_secs(4));
+
+        assert_eq!(transcript.plays.len(), 1);
+        assert_eq!(transcript.plays[0].time, Duration::from_secs(4));
+        let read_back: Transcript = transcript.to_string().parse().unwrap();
+        assert_eq!(read_back.final_game().unwrap().in_play(), game.in_play());
+    }
+
+    #[test]
+    fn transcripts_are_written_and_checked() {
+        let (mut transcript, _) = record_game(7, false);
+        transcript
+            .add_tag("Player", "Ada \"the \\ counter\"")
+            .unwrap();
+        transcript.add_tag("Notes", "Two lines,\nthen\r\n").unwrap();
+        for name in ["", "Two words", "Tag]", "Deck"] {
+            assert_eq!(
+                transcript.add_tag(name, "value"),
+                Err(TranscriptError::TagName(name.to_string()))
+            );
+        }
+        assert_eq!(transcript.tags().len(), 2);
+        let text = format!("; A comment\n{}", transcript);
+
+        assert!(text.contains("\n[Variant \"Set\"]\n"));
+        assert!(text.contains("\n[GuaranteeMove \"no\"]\n"));
+        assert!(text.contains("\n[Player \"Ada \\\"the \\\\ counter\\\"\"]\n"));
+        assert!(text.contains("\n[Notes \"Two lines,\\nthen\\r\\n\"]\n"));
+        assert!(text.contains("\n1. 1.234 "));
+        assert_eq!(text.parse(), Ok(transcript.clone()));
+
+        let without_deck: String = text
+            .lines()
+            .filter(|line| !line.starts_with("[Deck"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert_eq!(
+            without_deck.parse::<Transcript>(),
+            Err(TranscriptError::MissingTag("Deck"))
+        );
+        assert!(matches!(
+            text.replace("\n2. ", "\n3. ").parse::<Transcript>(),
+            Err(TranscriptError::Syntax { .. })
+        ));
+        for tag in ["[Deck \"0000\"]", "[ \"value\"]", "[Tag] \"value\"]"] {
+            assert!(matches!(
+                text.replace("[Player", &format!("{}\n[Player", tag))
+                    .parse::<Transcript>(),
+                Err(TranscriptError::Syntax { .. })
+            ));
+        }
+
+        // Cards can only be played once
+        let first = transcript
+            .plays
+            .iter()
+            .position(|play| !play.cards.is_empty())
+            .unwrap();
+        let mut played_twice = transcript.clone();
+        played_twice
+            .plays
+            .insert(first + 1, transcript.plays[first].clone());
+        assert_eq!(
+            played_twice.final_game().err(),
+            Some(TranscriptError::IllegalPlay(first + 2))
+        );
+    }
+}
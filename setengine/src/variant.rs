@@ -0,0 +1,84 @@
+use crate::cards::{find_set, find_ultraset};
+use crate::{selection_is_set, selection_is_ultraset, CardCoordinates, GameRules};
+use cardgen::CardVisualAttr;
+
+/// A game played by picking cards from a board, such as Set or UltraSet. Implement this to
+/// play a new game with `GameDeck`.
+pub trait GameVariant {
+    /// The cards the game is played with.
+    type Card: Copy;
+
+    /// Number of cards in a selection, or the largest number if plays vary in size.
+    fn selection_size(&self) -> usize;
+
+    /// Smallest number of cards in a selection, for games where plays vary in size.
+    fn min_selection_size(&self) -> usize {
+        self.selection_size()
+    }
+
+    /// Checks if the selected cards form a valid play.
+    fn is_valid(&self, cards: &[Self::Card]) -> bool;
+
+    /// Returns the indices of a valid play among `cards`, if there is one.
+    fn find_move(&self, cards: &[Self::Card]) -> Option<Vec<usize>>;
+
+    /// Checks if `cards` contain a valid play.
+    fn contains_move(&self, cards: &[Self::Card]) -> bool {
+        self.find_move(cards).is_some()
+    }
+
+    /// Returns the indices of cards to reveal as a hint: all but one card of a valid play.
+    fn hint(&self, cards: &[Self::Card]) -> Option<Vec<usize>> {
+        self.find_move(cards).map(|mut indices| {
+            indices.pop();
+            indices
+        })
+    }
+
+    /// The rules the game is dealt with unless others are given.
+    fn default_rules(&self) -> GameRules {
+        GameRules::set()
+    }
+}
+
+/// The games played with a deck of cards in F_3^N
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetVariant<const N: usize = 4> {
+    /// Find three cards that form a set
+    Set,
+    /// Find four cards, two pairs of which complete to the same card
+    UltraSet,
+}
+
+impl<const N: usize> GameVariant for SetVariant<N> {
+    type Card = (CardCoordinates<N>, CardVisualAttr);
+
+    fn selection_size(&self) -> usize {
+        match self {
+            SetVariant::Set => 3,
+            SetVariant::UltraSet => 4,
+        }
+    }
+
+    fn is_valid(&self, cards: &[Self::Card]) -> bool {
+        match self {
+            SetVariant::Set => selection_is_set(cards),
+            SetVariant::UltraSet => selection_is_ultraset(cards),
+        }
+    }
+
+    fn find_move(&self, cards: &[Self::Card]) -> Option<Vec<usize>> {
+        match self {
+            SetVariant::Set => find_set(cards).map(|indices| indices.to_vec()),
+            SetVariant::UltraSet => find_ultraset(cards).map(|indices| indices.to_vec()),
+        }
+    }
+
+    fn default_rules(&self) -> GameRules {
+        match self {
+            SetVariant::Set => GameRules::set(),
+            SetVariant::UltraSet => GameRules::ultraset(),
+        }
+    }
+}